@@ -0,0 +1,172 @@
+use crate::chart::base::{format_number, Axis, BaseChart, Chart};
+use crate::data::{PVec2, Vec2};
+use crate::renderer::canvas::Canvas;
+use crate::renderer::color::Style;
+use crate::renderer::dots::DotMode;
+use crate::renderer::scene::{Scene, Shape};
+use crate::renderer::shapes::ScaledViewBox;
+
+/// Half of the width of a bar, relative to the space given to each category.
+const BAR_HALF_WIDTH: f32 = 0.35;
+
+/// Direction that the bars of a bar chart extend in.
+#[derive(PartialEq, Copy, Clone)]
+pub enum Orientation {
+    /// Categories are placed along the X axis, and bars extend up (or down, for negative values).
+    Vertical,
+    /// Categories are placed along the Y axis, and bars extend right (or left, for negative
+    /// values).
+    Horizontal,
+}
+
+/// Bar chart. Plots a list of labeled categories as filled bars, with the value of each category
+/// written next to its bar.
+pub struct BarChart {
+    base: BaseChart,
+    bars: Vec<(String, f32)>,
+    orientation: Orientation,
+    symbol: char,
+}
+
+impl BarChart {
+    /// Create a new bar chart from a list of labels and values. The value axis always includes
+    /// zero, since that's where the bars start.
+    pub fn new(canvas: Box<dyn Canvas>, bars: Vec<(String, f32)>, orientation: Orientation) -> BarChart {
+        let mut base: BaseChart = BaseChart::new(canvas);
+        let min: f32 = bars.iter().map(|b| b.1).fold(0.0, f32::min);
+        let max: f32 = bars.iter().map(|b| b.1).fold(0.0, f32::max);
+        let mut labels: Vec<String> = bars.iter().map(|b| b.0.clone()).collect();
+        let values: Axis = Axis::fit("", min, max);
+        let negative: bool = min < 0.0;
+        // leave room for the annotations past the end of each bar
+        match orientation {
+            Orientation::Vertical => {
+                base.set_x(Axis::Category { name: "".to_string(), labels });
+                base.set_y(values);
+                base.set_padding([1, 0, negative as u16, 0]);
+            }
+            Orientation::Horizontal => {
+                // the first category goes at the top
+                labels.reverse();
+                let width: u16 = bars.iter().map(|b| format_number(b.1).len()).max().unwrap_or(0) as u16 + 1;
+                base.set_x(values);
+                base.set_y(Axis::Category { name: "".to_string(), labels });
+                base.set_padding([0, width, 0, if negative { width } else { 0 }]);
+            }
+        }
+        BarChart { base, bars, orientation, symbol: '#' }
+    }
+
+    /// Set how bars are drawn. In modes other than `DotMode::Ascii`, bars are filled with dots,
+    /// so their ends don't have to line up with whole cells.
+    pub fn dots(mut self, mode: DotMode) -> BarChart {
+        self.base.set_mode(mode);
+        self
+    }
+
+    /// Get the position of a category along the category axis. In horizontal charts, the first
+    /// category is placed at the top.
+    fn category_position(&self, index: usize) -> f32 {
+        match self.orientation {
+            Orientation::Vertical => index as f32,
+            Orientation::Horizontal => (self.bars.len() - 1 - index) as f32,
+        }
+    }
+
+    /// Get the start of a piece of text that is centered on a point, keeping it within the plot.
+    fn centered(text: &str, center: u16, width: u16) -> u16 {
+        let len: u16 = text.chars().count() as u16;
+        center.saturating_sub(len / 2).min(width.saturating_sub(len))
+    }
+}
+
+impl Chart for BarChart {
+    fn base(&self) -> &BaseChart {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut BaseChart {
+        &mut self.base
+    }
+
+    /// Lay out the chart as a scene.
+    fn scene(&self) -> Scene {
+        let mut scene: Scene = self.base.scene();
+        self.base.axes(&mut scene);
+        let viewbox: ScaledViewBox = self.base.viewbox();
+        let width: u16 = scene.width;
+        let slot: usize = (self.base.data_area().1.x as usize / self.bars.len().max(1)).max(2) - 1;
+        let bars: Vec<Shape> = self.bars.iter().enumerate().map(|(i, (_, value))| {
+            let position: f32 = self.category_position(i);
+            let (start, size) = match self.orientation {
+                Orientation::Vertical => (Vec2::new(position - BAR_HALF_WIDTH, 0.0), Vec2::new(BAR_HALF_WIDTH * 2.0, *value)),
+                Orientation::Horizontal => (Vec2::new(0.0, position - BAR_HALF_WIDTH), Vec2::new(*value, BAR_HALF_WIDTH * 2.0)),
+            };
+            Shape::Rect(viewbox.translate(start), viewbox.translate(start + size))
+        }).collect();
+        scene.shapes(bars, self.symbol, self.base.mode(), self.base.style(0));
+        // annotations go on top of the bars
+        for (i, (_, value)) in self.bars.iter().enumerate() {
+            let position: f32 = self.category_position(i);
+            let annotation: String = format_number(*value);
+            match self.orientation {
+                Orientation::Vertical => {
+                    let end: PVec2 = viewbox.translate_to_plot(Vec2::new(position, *value));
+                    let annotation: String = annotation.chars().take(slot).collect();
+                    let row: u16 = if *value < 0.0 { end.y + 1 } else { end.y.saturating_sub(1) };
+                    scene.text(&annotation, PVec2::new(Self::centered(&annotation, end.x, width), row), Style::PLAIN);
+                }
+                Orientation::Horizontal => {
+                    let end: PVec2 = viewbox.translate_to_plot(Vec2::new(*value, position));
+                    let column: u16 = if *value < 0.0 { end.x.saturating_sub(annotation.len() as u16 + 1) } else { end.x + 2 };
+                    scene.text(&annotation, PVec2::new(column, end.y), Style::PLAIN);
+                }
+            }
+        }
+        scene
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{ColumnRef, Table};
+    use crate::snapshot::{assert_chart, canvas, fixture};
+
+    fn fruit() -> Vec<(String, f32)> {
+        let table: Table = fixture("fruit.txt", "whitespace");
+        let labels: Vec<String> = table.labels(&ColumnRef::Name("fruit".to_string())).unwrap();
+        let values: Vec<f32> = table.numbers(&ColumnRef::Name("count".to_string())).unwrap();
+        labels.into_iter().zip(values).collect()
+    }
+
+    #[test]
+    fn bar_chart_vertical() {
+        let chart: BarChart = BarChart::new(canvas(50, 16), fruit(), Orientation::Vertical);
+        let lines: Vec<String> = assert_chart("bar_chart_vertical", &chart);
+        // values are written above positive bars and below negative ones
+        let row = |label: &str| lines.iter().position(|l| l.split_whitespace().any(|word| word == label)).unwrap();
+        assert!(row("20") < row("12") && row("12") < row("7.5") && row("7.5") < row("3"));
+        assert_eq!(row("-4"), lines.len() - 3);
+    }
+
+    #[test]
+    fn bar_chart_horizontal() {
+        let mut chart: BarChart = BarChart::new(canvas(50, 12), fruit(), Orientation::Horizontal);
+        chart.base_mut().set_names("count", "fruit");
+        let lines: Vec<String> = assert_chart("bar_chart_horizontal", &chart);
+        // negative values are labelled on the left of their bar
+        let plum: &String = lines.iter().find(|l| l.contains("plum")).unwrap();
+        assert!(plum.find("-4").unwrap() < plum.find('#').unwrap());
+    }
+
+    #[test]
+    fn bar_chart_quadrants() {
+        let chart: BarChart = BarChart::new(canvas(50, 12), fruit(), Orientation::Horizontal)
+            .dots(DotMode::Quadrant);
+        let lines: Vec<String> = assert_chart("bar_chart_quadrants", &chart);
+        // 7.5 ends halfway through a cell, so the bar ends with the left half of one
+        let oranges: &String = lines.iter().find(|l| l.contains("oranges")).unwrap();
+        assert!(oranges.contains("\u{2588}\u{258c} 7.5"));
+    }
+}
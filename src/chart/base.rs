@@ -1,6 +1,92 @@
-use crate::renderer::plot::Plot;
-use crate::renderer::shapes::ScaledViewBox;
+use crate::data::{PVec2, Series, Vec2};
+use crate::chart::legend::{column, column_size, emptiest_corner, row, row_width, Entry, LegendPosition};
+use crate::chart::time::{timestamp, TimeStep};
+use crate::renderer::canvas::Canvas;
+use crate::renderer::color::{Color, Style, AXIS_COLOR, PALETTE};
+use crate::renderer::dots::DotMode;
+use crate::renderer::scene::{Scene, Shape};
+use crate::renderer::shapes::{Scale, ScaledViewBox};
 
+/// Default markers for each series, used in order.
+pub const DEFAULT_MARKERS: &str = "*o+x#@";
+
+/// Widest that tick labels on the Y axis are allowed to be.
+const MAX_Y_LABEL_WIDTH: usize = 10;
+
+/// Roughly how many columns to leave between ticks on scaled X axes.
+const COLUMNS_PER_TICK: f32 = 10.0;
+
+/// Roughly how many rows to leave between ticks on scaled Y axes.
+const ROWS_PER_TICK: f32 = 3.0;
+
+/// Fraction of the range of the data to leave empty on each side of a scaled axis.
+const SCALED_PADDING: f32 = 0.05;
+
+/// Format a number for display on a chart, using as few decimal places as needed (up to 2).
+pub fn format_number(n: f32) -> String {
+    let formatted: String = format!("{:.2}", n);
+    let trimmed: &str = formatted.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" { "0".to_string() } else { trimmed.to_string() }
+}
+
+/// Get the number of decimal places needed to show multiples of a step size exactly.
+fn decimals_for(step: f32) -> usize {
+    (0..6).find(|d| {
+        let scaled: f32 = step.abs() * 10f32.powi(*d as i32);
+        (scaled - scaled.round()).abs() < 1e-3 * scaled.max(1.0)
+    }).unwrap_or(6)
+}
+
+/// Format a tick label so that it fits within a width, if possible. Large numbers are shortened
+/// with SI suffixes (k, M, G, T), and anything else that is too wide uses scientific notation.
+fn format_tick(value: f32, decimals: usize, max_width: usize) -> String {
+    // avoid showing "-0"
+    let value: f32 = if value == 0.0 { 0.0 } else { value };
+    let fixed: String = format!("{:.*}", decimals, value);
+    if fixed.len() <= max_width { return fixed }
+    for (scale, suffix) in [(1e12, "T"), (1e9, "G"), (1e6, "M"), (1e3, "k")] {
+        if value.abs() >= scale {
+            let short: String = format_number(value / scale) + suffix;
+            if short.len() <= max_width { return short }
+            break
+        }
+    }
+    format!("{:.1e}", value)
+}
+
+/// Get a "nice" step size: 1, 2 or 5 times a power of 10. Each step of `k` moves to the next
+/// nice number, so `k = 0` is 1, `k = 1` is 2, `k = 3` is 10, and `k = -1` is 0.5.
+fn nice_step(k: i32) -> f32 {
+    let mantissa: f32 = [1.0, 2.0, 5.0][k.rem_euclid(3) as usize];
+    let power: i32 = k.div_euclid(3);
+    // divide for negative powers, so that steps like 0.05 are as close as possible to their
+    // decimal value
+    if power < 0 { mantissa / 10f32.powi(-power) } else { mantissa * 10f32.powi(power) }
+}
+
+/// Find the smallest nice step size that is at least as large as a value. Values that no step
+/// can match (zero, negative, infinite or NaN) get a step of 1.
+fn nice_step_index(at_least: f32) -> i32 {
+    if !at_least.is_finite() || at_least <= 0.0 { return 0 }
+    let mut k: i32 = (at_least.log10().floor() as i32) * 3;
+    while nice_step(k) < at_least { k += 1 }
+    while nice_step(k - 1) >= at_least { k -= 1 }
+    k
+}
+
+/// Cut off the end of a label if it is too long.
+fn clip(text: &str, max_len: usize) -> String {
+    text.chars().take(max_len).collect()
+}
+
+/// A marked position along an axis.
+pub struct Tick {
+    pub value: f32,
+    pub label: String,
+}
+
+/// Axis definition for a chart.
+#[derive(Clone)]
 pub enum Axis {
     Manual {
         name: String,
@@ -8,27 +94,797 @@ pub enum Axis {
         max: f32,
         markers: f32
     },
-    // Scaled {
-    //     name: String,
-    //     marker_scale: i8
-    // },
+    /// Axis with a labeled category at each whole number, starting from zero.
+    Category {
+        name: String,
+        labels: Vec<String>,
+    },
+    /// Axis that is fit to a range of data. Ticks are placed at a "nice" spacing (1, 2 or 5 times
+    /// a power of 10) that suits the space available. `marker_scale` adjusts the spacing; each
+    /// step up or down moves to the next smaller or larger nice spacing.
+    Scaled {
+        name: String,
+        marker_scale: i8,
+        min: f32,
+        max: f32,
+    },
+    /// Axis that is fit to a range of data on a logarithmic scale. Ticks are placed at powers of
+    /// the scale's base, every `markers` powers. Both ends of the range must be greater than zero.
+    Log {
+        name: String,
+        scale: Scale,
+        min: f32,
+        max: f32,
+        markers: f32,
+    },
+    /// Axis of times that is fit to a range of data. Values are seconds since `origin` (a Unix
+    /// timestamp), and ticks are placed on calendar boundaries, in UTC. Like `Axis::Scaled`,
+    /// `step` is picked to suit the space available when the axis is resolved; `marker_scale`
+    /// adjusts the spacing.
+    Time {
+        name: String,
+        marker_scale: i8,
+        origin: i64,
+        min: f32,
+        max: f32,
+        step: Option<TimeStep>,
+    },
+}
+
+/// Pad a range of values on both sides, so that nothing sits right on the edge of an axis. Empty
+/// ranges are widened first.
+fn pad(min: f32, max: f32) -> (f32, f32, f32) {
+    let (min, max) = if min == max { (min - 0.5, max + 0.5) } else { (min, max) };
+    (min, max, (max - min) * SCALED_PADDING)
+}
+
+impl Axis {
+    /// Create a scaled axis that fits a range of values. The range is padded, without letting
+    /// the padding cross zero, so that bars and areas can start at the axis.
+    pub fn fit(name: &str, min: f32, max: f32) -> Axis {
+        let (min, max, pad) = pad(min, max);
+        let min: f32 = if min >= 0.0 { (min - pad).max(0.0) } else { min - pad };
+        let max: f32 = if max <= 0.0 { (max + pad).min(0.0) } else { max + pad };
+        Axis::Scaled { name: name.to_string(), marker_scale: 0, min, max }
+    }
+
+    /// Create a logarithmic axis that fits a range of values. The range is padded on the
+    /// logarithmic scale.
+    pub fn fit_log(name: &str, scale: Scale, min: f32, max: f32) -> Axis {
+        let (min, max, pad) = pad(scale.apply(min), scale.apply(max));
+        Axis::Log { name: name.to_string(), scale, min: scale.invert(min - pad), max: scale.invert(max + pad), markers: 1.0 }
+    }
+
+    /// Create a time axis that fits a range of values, in seconds since `origin`. The range is
+    /// padded.
+    pub fn fit_time(name: &str, origin: i64, min: f32, max: f32) -> Axis {
+        let (min, max, pad) = pad(min, max);
+        Axis::Time { name: name.to_string(), marker_scale: 0, origin, min: min - pad, max: max + pad, step: None }
+    }
+
+    /// Get a copy of this axis covering a different range of values. Category axes are
+    /// unchanged.
+    pub fn with_bounds(&self, new_min: f32, new_max: f32) -> Axis {
+        let mut axis: Axis = self.clone();
+        if let Axis::Manual { min, max, .. } | Axis::Scaled { min, max, .. } | Axis::Log { min, max, .. }
+            | Axis::Time { min, max, .. } = &mut axis {
+            (*min, *max) = (new_min, new_max);
+        }
+        axis
+    }
+
+    /// Get a copy of this axis that is moved along by `shift` times the length of its range, and
+    /// then grown or shrunk around its center by `zoom`, on the axis's scale. Category axes are
+    /// unchanged, and so is any axis that would become too small to show.
+    pub fn moved(&self, shift: f32, zoom: f32) -> Axis {
+        let scale: Scale = self.scale();
+        let (min, max) = self.bounds();
+        let (min, max) = (scale.apply(min), scale.apply(max));
+        let center: f32 = (min + max) / 2.0 + (max - min) * shift;
+        let half: f32 = (max - min) / 2.0 * zoom;
+        // stop before the range is lost to rounding errors
+        if !half.is_finite() || half <= center.abs() * 1e-5 { return self.clone() }
+        self.with_bounds(scale.invert(center - half), scale.invert(center + half))
+    }
+
+    /// Get the scale that values are spread along this axis with.
+    pub fn scale(&self) -> Scale {
+        match self {
+            Axis::Log { scale, .. } => *scale,
+            _ => Scale::Linear,
+        }
+    }
+
+    /// Change the tick spacing of a scaled or time axis (see `Axis::Scaled`). Other axes are
+    /// unchanged.
+    pub fn with_marker_scale(mut self, scale: i8) -> Axis {
+        if let Axis::Scaled { marker_scale, .. } | Axis::Time { marker_scale, .. } = &mut self { *marker_scale = scale; }
+        self
+    }
+
+    /// Turn a scaled axis into a manual one, with ticks suited to the length of the axis (in
+    /// cells). `cells_per_tick` is roughly how far apart ticks should be; if `fit_labels` is set,
+    /// ticks are also spread out until their labels fit side by side. Other axes are unchanged.
+    pub fn resolve(&self, length: u16, cells_per_tick: f32, fit_labels: bool) -> Axis {
+        if let Axis::Log { name, scale, min, max, .. } = self {
+            return Self::resolve_log(name, *scale, *min, *max, length, cells_per_tick)
+        }
+        if let Axis::Time { step: None, .. } = self {
+            return self.resolve_time(length, cells_per_tick, fit_labels)
+        }
+        let Axis::Scaled { name, marker_scale, min, max } = self else { return self.clone() };
+        let (min, max) = (*min, *max);
+        let count: f32 = (length as f32 / cells_per_tick).max(1.0);
+        let mut k: i32 = nice_step_index((max - min) / count) - *marker_scale as i32;
+        loop {
+            let step: f32 = nice_step(k);
+            let axis: Axis = Axis::Manual {
+                name: name.clone(),
+                min,
+                max,
+                markers: step,
+            };
+            let ticks: Vec<Tick> = axis.ticks(length as usize);
+            let widest: usize = ticks.iter().map(|t| t.label.chars().count()).max().unwrap_or(0);
+            if !fit_labels || (widest + 1) * ticks.len() <= length as usize || ticks.len() <= 2 {
+                return axis
+            }
+            k += 1;
+        }
+    }
+
+    /// Space out the ticks of a logarithmic axis so that there are no more than fit along it.
+    fn resolve_log(name: &str, scale: Scale, min: f32, max: f32, length: u16, cells_per_tick: f32) -> Axis {
+        let powers: f32 = scale.apply(max) - scale.apply(min);
+        let count: f32 = (length as f32 / cells_per_tick).max(1.0);
+        Axis::Log { name: name.to_string(), scale, min, max, markers: (powers / count).ceil().max(1.0) }
+    }
+
+    /// Pick a calendar step for the ticks of a time axis. Works the same way as for scaled axes.
+    fn resolve_time(&self, length: u16, cells_per_tick: f32, fit_labels: bool) -> Axis {
+        let Axis::Time { name, marker_scale, origin, min, max, .. } = self else { return self.clone() };
+        let (min, max) = (*min, *max);
+        let count: f32 = (length as f32 / cells_per_tick).max(1.0);
+        let first: i32 = TimeStep::index_at_least(((max - min) / count) as f64) as i32 - *marker_scale as i32;
+        let mut index: usize = first.max(0) as usize;
+        loop {
+            let axis: Axis = Axis::Time {
+                name: name.clone(),
+                marker_scale: *marker_scale,
+                origin: *origin,
+                min,
+                max,
+                step: Some(TimeStep::nth(index)),
+            };
+            let ticks: Vec<Tick> = axis.ticks(length as usize);
+            let widest: usize = ticks.iter().map(|t| t.label.chars().count()).max().unwrap_or(0);
+            if !fit_labels || (widest + 1) * ticks.len() <= length as usize || ticks.len() <= 2 {
+                return axis
+            }
+            index += 1;
+        }
+    }
+
+    /// Get the label for a tick on a logarithmic axis, at a power of the scale's base.
+    fn log_label(scale: Scale, power: i32, max_width: usize) -> String {
+        let value: f32 = scale.invert(power as f32);
+        match scale {
+            Scale::Ln => match power {
+                0 => "1".to_string(),
+                1 => "e".to_string(),
+                _ => format!("e^{}", power),
+            },
+            // fractions of powers of two get long, so show them as powers
+            Scale::Log2 if power < 0 => format!("2^{}", power),
+            _ => format_tick(value, decimals_for(value), max_width),
+        }
+    }
+
+    /// Get the name of this axis.
+    pub fn name(&self) -> &str {
+        match self {
+            Axis::Manual { name, .. } | Axis::Category { name, .. } | Axis::Scaled { name, .. }
+            | Axis::Log { name, .. } | Axis::Time { name, .. } => name,
+        }
+    }
+
+    /// Set the name of this axis.
+    pub fn set_name(&mut self, new_name: &str) {
+        match self {
+            Axis::Manual { name, .. } | Axis::Category { name, .. } | Axis::Scaled { name, .. }
+            | Axis::Log { name, .. } | Axis::Time { name, .. } => {
+                *name = new_name.to_string()
+            }
+        }
+    }
+
+    /// Get the minimum and maximum values of this axis. Scaled axes should be resolved first, to
+    /// get their bounds after padding and rounding.
+    pub fn bounds(&self) -> (f32, f32) {
+        match self {
+            Axis::Manual { min, max, .. } | Axis::Scaled { min, max, .. } | Axis::Log { min, max, .. }
+            | Axis::Time { min, max, .. } => {
+                (*min, *max)
+            }
+            Axis::Category { labels, .. } => (-0.5, labels.len() as f32 - 0.5),
+        }
+    }
+
+    /// Get the exact label of a value on this axis, such as for reading off a single point: the
+    /// date and time on time axes, the label of the nearest category on category axes, and the
+    /// number in full on other axes.
+    pub fn value_label(&self, value: f32) -> String {
+        match self {
+            Axis::Time { origin, .. } => timestamp(*origin as f64 + value as f64),
+            Axis::Category { labels, .. } if value >= -0.5 => labels.get(value.round() as usize).cloned().unwrap_or_default(),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Get the ticks along this axis. Labels are kept within `max_width` characters where
+    /// possible.
+    pub fn ticks(&self, max_width: usize) -> Vec<Tick> {
+        match self {
+            Axis::Manual { min, max, markers, .. } => {
+                // don't let a bad marker distance create an endless number of ticks
+                if *markers <= 0.0 || (max - min) / markers > 1000.0 {
+                    return [*min, *max].iter().map(|v| Tick { value: *v, label: format_tick(*v, 2, max_width) }).collect()
+                }
+                let decimals: usize = decimals_for(*markers);
+                let first: i64 = (min / markers - 1e-4).ceil() as i64;
+                let last: i64 = (max / markers + 1e-4).floor() as i64;
+                (first..=last).map(|i| {
+                    let value: f32 = i as f32 * markers;
+                    Tick { value, label: format_tick(value, decimals, max_width) }
+                }).collect()
+            }
+            Axis::Category { labels, .. } => labels.iter().enumerate()
+                .map(|(i, label)| Tick { value: i as f32, label: clip(label, max_width) })
+                .collect(),
+            Axis::Scaled { .. } => self.resolve(max_width as u16, COLUMNS_PER_TICK, false).ticks(max_width),
+            Axis::Log { scale, min, max, markers, .. } => {
+                let first: i32 = (scale.apply(*min) - 1e-4).ceil() as i32;
+                let last: i32 = (scale.apply(*max) + 1e-4).floor() as i32;
+                let every: i32 = (*markers as i32).max(1);
+                // if the range is too small to fit two powers, use linear ticks instead
+                if last - first < 1 {
+                    return Axis::fit("", *min, *max).resolve(max_width as u16, COLUMNS_PER_TICK, false).ticks(max_width)
+                }
+                (first..=last).filter(|p| p.rem_euclid(every) == 0)
+                    .map(|p| Tick { value: scale.invert(p as f32), label: Self::log_label(*scale, p, max_width) })
+                    .collect()
+            }
+            Axis::Time { step: None, .. } => self.resolve(max_width as u16, COLUMNS_PER_TICK, false).ticks(max_width),
+            Axis::Time { origin, min, max, step: Some(step), .. } => {
+                let origin: f64 = *origin as f64;
+                step.boundaries(origin + *min as f64, origin + *max as f64).into_iter()
+                    .map(|t| Tick { value: (t - origin) as f32, label: clip(&step.label(t), max_width) })
+                    .collect()
+            }
+        }
+    }
 }
 
-/// Base struct for a chart.
+/// Where everything is placed on a chart.
+struct Layout {
+    /// X axis, with automatic scaling resolved.
+    x: Axis,
+    /// Y axis, with automatic scaling resolved.
+    y: Axis,
+    /// Position of the area enclosed by the axes.
+    position: PVec2,
+    /// Size of the area enclosed by the axes.
+    size: PVec2,
+}
+
+/// Base struct for a chart. Handles the axes, the title, and the layout of the plot area.
 pub struct BaseChart {
-    plot: Plot,
+    canvas: Box<dyn Canvas>,
     title: String,
     x: Axis,
     y: Axis,
+    markers: Vec<char>,
+    colors: Vec<Color>,
+    /// Empty space kept inside the axes (top, right, bottom, left).
+    padding: [u16; 4],
+    /// Scales used for the X and Y axes when fitting them to data.
+    scales: (Scale, Scale),
+    /// If set, the X axis is fit as a time axis, with values in seconds since this Unix
+    /// timestamp.
+    time_origin: Option<i64>,
+    legend: LegendPosition,
+    /// Names of the series drawn on the chart, to list in the legend.
+    names: Vec<String>,
+    /// How the data is drawn: with markers, or with dots.
+    mode: DotMode,
 }
 
 impl BaseChart {
-    pub fn new(plot: Plot) -> BaseChart {
+    pub fn new(canvas: Box<dyn Canvas>) -> BaseChart {
         BaseChart {
-            plot,
+            canvas,
             title: "".to_string(),
             x: Axis::Manual{ name: "".to_string(), min: 0.0, max: 1.0, markers: 0.2 },
-            y: Axis::Manual{ name: "".to_string(), min: 0.0, max: 1.0, markers: 0.2 }
+            y: Axis::Manual{ name: "".to_string(), min: 0.0, max: 1.0, markers: 0.2 },
+            markers: DEFAULT_MARKERS.chars().collect(),
+            colors: PALETTE.to_vec(),
+            padding: [0; 4],
+            scales: (Scale::Linear, Scale::Linear),
+            time_origin: None,
+            legend: LegendPosition::Auto,
+            names: Vec::new(),
+            mode: DotMode::Ascii,
+        }
+    }
+
+    /// Get the canvas this chart draws on.
+    pub fn canvas(&self) -> &dyn Canvas {
+        self.canvas.as_ref()
+    }
+
+    /// Change the size of the canvas, in cells. The layout is worked out again the next time the
+    /// chart is drawn.
+    pub fn set_size(&mut self, width: u16, height: u16) {
+        self.canvas.set_size(width, height);
+    }
+
+    /// Set the title of the chart.
+    pub fn set_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    /// Set the X axis. The name of the current axis is kept if the new one doesn't have one.
+    pub fn set_x(&mut self, mut axis: Axis) {
+        if axis.name().is_empty() { axis.set_name(self.x.name()); }
+        self.x = axis;
+    }
+
+    /// Set the Y axis. The name of the current axis is kept if the new one doesn't have one.
+    pub fn set_y(&mut self, mut axis: Axis) {
+        if axis.name().is_empty() { axis.set_name(self.y.name()); }
+        self.y = axis;
+    }
+
+    /// Get the X axis.
+    pub fn x(&self) -> &Axis {
+        &self.x
+    }
+
+    /// Get the Y axis.
+    pub fn y(&self) -> &Axis {
+        &self.y
+    }
+
+    /// Move the range of values shown on both axes by a fraction of its length (`shift`), and
+    /// then grow or shrink it around its center by `zoom` (see `Axis::moved`).
+    pub fn move_view(&mut self, shift: Vec2, zoom: f32) {
+        self.x = self.x.moved(shift.x, zoom);
+        self.y = self.y.moved(shift.y, zoom);
+    }
+
+    /// Set the names of the X and Y axes.
+    pub fn set_names(&mut self, x: &str, y: &str) {
+        self.x.set_name(x);
+        self.y.set_name(y);
+    }
+
+    /// Set the markers to use for each series. If there are more series than markers, the markers
+    /// are reused from the start.
+    pub fn set_markers(&mut self, markers: &str) {
+        if !markers.is_empty() { self.markers = markers.chars().collect(); }
+    }
+
+    /// Get the marker for a series.
+    pub fn marker(&self, index: usize) -> char {
+        self.markers[index % self.markers.len()]
+    }
+
+    /// Set the colors to use for each series. If there are more series than colors, the colors
+    /// are reused from the start.
+    pub fn set_colors(&mut self, colors: &[Color]) {
+        if !colors.is_empty() { self.colors = colors.to_vec(); }
+    }
+
+    /// Get the style to draw a series with.
+    pub fn style(&self, index: usize) -> Style {
+        Style::color(self.colors[index % self.colors.len()])
+    }
+
+    /// Set how much empty space to keep between the axes and the data (top, right, bottom, left).
+    /// Useful for leaving room for annotations next to the data.
+    pub fn set_padding(&mut self, padding: [u16; 4]) {
+        self.padding = padding;
+    }
+
+    /// Set the tick spacing adjustment of both axes, if they are scaled (see `Axis::Scaled`).
+    pub fn set_marker_scale(&mut self, marker_scale: i8) {
+        self.x = self.x.clone().with_marker_scale(marker_scale);
+        self.y = self.y.clone().with_marker_scale(marker_scale);
+    }
+
+    /// Set the scales to use for the X and Y axes the next time they are fit to data.
+    pub fn set_scales(&mut self, x: Scale, y: Scale) {
+        self.scales = (x, y);
+    }
+
+    /// Fit the X axis as a time axis the next time the axes are fit to data, with values in
+    /// seconds since a Unix timestamp. `None` goes back to using the X scale.
+    pub fn set_time_origin(&mut self, origin: Option<i64>) {
+        self.time_origin = origin;
+    }
+
+    /// Set how the data is drawn. In modes other than `DotMode::Ascii`, it is drawn with dots
+    /// instead of markers.
+    pub fn set_mode(&mut self, mode: DotMode) {
+        self.mode = mode;
+    }
+
+    /// Get how the data is drawn.
+    pub fn mode(&self) -> DotMode {
+        self.mode
+    }
+
+    /// Set where to put the legend.
+    pub fn set_legend(&mut self, legend: LegendPosition) {
+        self.legend = legend;
+    }
+
+    /// Set the names of the series drawn on the chart, in order, to list in the legend.
+    pub fn set_series_names(&mut self, names: Vec<String>) {
+        self.names = names;
+    }
+
+    /// Get where the legend goes. Charts without any named series have no legend, and with the
+    /// automatic position, neither do charts with only one.
+    fn legend_position(&self) -> LegendPosition {
+        match self.legend {
+            _ if self.names.is_empty() => LegendPosition::None,
+            LegendPosition::Auto if self.names.len() < 2 => LegendPosition::None,
+            legend => legend,
+        }
+    }
+
+    /// Create an axis that fits a range of values on a scale. Logarithmic axes use the smallest
+    /// positive value as their minimum, since they can't show anything at or below zero.
+    fn fit_axis(scale: Scale, min: f32, min_positive: f32, max: f32) -> Axis {
+        match scale {
+            Scale::Linear => Axis::fit("", min, max),
+            // no positive values to show
+            _ if max <= 0.0 => Axis::fit_log("", scale, 1.0, 10.0),
+            _ => Axis::fit_log("", scale, min_positive, max),
+        }
+    }
+
+    /// Set the X and Y axes so that they cover all the given points.
+    pub fn fit<'a>(&mut self, points: impl IntoIterator<Item = &'a Vec2>) {
+        let mut min: Vec2 = Vec2::new(f32::INFINITY, f32::INFINITY);
+        let mut max: Vec2 = Vec2::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+        let mut min_positive: Vec2 = Vec2::new(f32::INFINITY, f32::INFINITY);
+        for point in points {
+            min = Vec2::new(min.x.min(point.x), min.y.min(point.y));
+            max = Vec2::new(max.x.max(point.x), max.y.max(point.y));
+            if point.x > 0.0 { min_positive.x = min_positive.x.min(point.x); }
+            if point.y > 0.0 { min_positive.y = min_positive.y.min(point.y); }
+        }
+        // nothing to fit
+        if min.x > max.x { return }
+        self.set_x(match self.time_origin {
+            Some(origin) => Axis::fit_time("", origin, min.x, max.x),
+            None => Self::fit_axis(self.scales.0, min.x, min_positive.x, max.x),
+        });
+        self.set_y(Self::fit_axis(self.scales.1, min.y, min_positive.y, max.y));
+    }
+
+    /// Fit the axes to the points in some series, and list the series in the legend.
+    pub fn fit_series(&mut self, series: &[Series]) {
+        self.fit(series.iter().flat_map(|s| s.points.iter()));
+        self.set_series_names(series.iter().map(|s| s.name.clone()).collect());
+    }
+
+    /// Set the scales of the X and Y axes, and fit the axes to some points again. Points that
+    /// can't be shown on a logarithmic scale are left out.
+    pub fn fit_scales<'a>(&mut self, x: Scale, y: Scale, points: impl IntoIterator<Item = &'a Vec2>) {
+        self.set_scales(x, y);
+        self.fit(points);
+    }
+
+    /// Use a time axis for X, with X values in seconds since a Unix timestamp, and fit the axes
+    /// to some points again.
+    pub fn fit_time<'a>(&mut self, origin: i64, points: impl IntoIterator<Item = &'a Vec2>) {
+        self.set_time_origin(Some(origin));
+        self.fit(points);
+    }
+
+    /// Check if a point can be shown on the scales of the axes.
+    pub fn can_show(&self, point: &Vec2) -> bool {
+        self.x.scale().contains(point.x) && self.y.scale().contains(point.y)
+    }
+
+    /// Get the widest that Y axis tick labels may be.
+    fn y_label_width(&self) -> usize {
+        MAX_Y_LABEL_WIDTH.min(self.canvas.width() as usize / 4)
+    }
+
+    /// Get the position and size of the data area; this is the plot area, minus space for the
+    /// title, axes, tick labels, axis names and padding.
+    pub fn data_area(&self) -> (PVec2, PVec2) {
+        Self::inside_padding(&self.layout(), self.padding)
+    }
+
+    /// Get the area inside the padding of a layout.
+    fn inside_padding(layout: &Layout, padding: [u16; 4]) -> (PVec2, PVec2) {
+        let [top, right, bottom, left] = padding;
+        (
+            PVec2::new(layout.position.x + left, layout.position.y + top),
+            PVec2::new(layout.size.x.saturating_sub(left + right).max(1), layout.size.y.saturating_sub(top + bottom).max(1)),
+        )
+    }
+
+    /// Lay out the chart. The Y axis is resolved first, since the width of its labels decides
+    /// how much space is left for the X axis.
+    fn layout(&self) -> Layout {
+        let [pad_top, pad_right, pad_bottom, pad_left] = self.padding;
+        let legend: LegendPosition = self.legend_position();
+        // title, legend and Y axis name go above the data area
+        let top: u16 = !self.title.is_empty() as u16 + (legend == LegendPosition::Top) as u16 + !self.y.name().is_empty() as u16;
+        // the axis line, tick labels and X axis name go below
+        let bottom: u16 = 2 + !self.x.name().is_empty() as u16;
+        let height: u16 = self.canvas.height().saturating_sub(top + bottom).max(1);
+        let y: Axis = self.y.resolve(height.saturating_sub(pad_top + pad_bottom), ROWS_PER_TICK, false);
+        // tick labels and the axis line go to the left
+        let label_width: u16 = y.ticks(self.y_label_width()).iter()
+            .map(|t| t.label.chars().count())
+            .max()
+            .unwrap_or(0) as u16;
+        let left: u16 = label_width + 1;
+        // a legend on the right is kept one column away from the data area
+        let right: u16 = if legend == LegendPosition::Right { column_size(&self.names).x + 1 } else { 0 };
+        let width: u16 = self.canvas.width().saturating_sub(left + right).max(1);
+        let x: Axis = self.x.resolve(width.saturating_sub(pad_left + pad_right), COLUMNS_PER_TICK, true);
+        Layout { x, y, position: PVec2::new(left, top), size: PVec2::new(width, height) }
+    }
+
+    /// Get a view box covering the data area of the chart, scaled to the bounds of the axes.
+    pub fn viewbox(&self) -> ScaledViewBox {
+        let layout: Layout = self.layout();
+        let (position, size) = Self::inside_padding(&layout, self.padding);
+        Self::layout_viewbox(&layout, position, size)
+    }
+
+    fn layout_viewbox(layout: &Layout, position: PVec2, size: PVec2) -> ScaledViewBox {
+        let (x_min, x_max) = layout.x.bounds();
+        let (y_min, y_max) = layout.y.bounds();
+        ScaledViewBox::new(position, size, x_min, x_max, y_min, y_max).scales(layout.x.scale(), layout.y.scale())
+    }
+
+    /// Get the start of a piece of text that is centered on a column, keeping it within the plot.
+    fn centered(&self, text: &str, center: u16) -> u16 {
+        let len: u16 = text.chars().count() as u16;
+        center.saturating_sub(len / 2).min(self.canvas.width().saturating_sub(len))
+    }
+
+    /// Create an empty scene covering the plot area.
+    pub fn scene(&self) -> Scene {
+        Scene::new(self.canvas.width(), self.canvas.height())
+    }
+
+    /// Add the title, both axes, their ticks and tick labels, and their names to a scene.
+    pub fn axes(&self, scene: &mut Scene) {
+        let layout: Layout = self.layout();
+        let (position, size) = (layout.position, layout.size);
+        let (data_position, data_size) = Self::inside_padding(&layout, self.padding);
+        let viewbox: ScaledViewBox = Self::layout_viewbox(&layout, data_position, data_size);
+        let axis_style: Style = Style::color(AXIS_COLOR);
+        let axis_x: u16 = position.x - 1;
+        let axis_y: u16 = position.y + size.y;
+        let right: u16 = position.x + size.x - 1;
+        let center: u16 = position.x + size.x / 2;
+        let cell = |x: u16, y: u16| Vec2::new(x as f32, y as f32);
+        let mut row: u16 = 0;
+        if !self.title.is_empty() {
+            scene.text(&self.title, PVec2::new(self.centered(&self.title, center), row), Style::PLAIN.bold());
+            row += 1;
+        }
+        if self.legend_position() == LegendPosition::Top { row += 1; }
+        if !self.y.name().is_empty() {
+            scene.text(self.y.name(), PVec2::new(0, row), Style::PLAIN);
+        }
+
+        // axis lines
+        scene.shapes(vec![Shape::Line(cell(axis_x, position.y), cell(axis_x, axis_y))], '|', DotMode::Ascii, axis_style);
+        scene.shapes(vec![Shape::Line(cell(axis_x, axis_y), cell(right, axis_y))], '-', DotMode::Ascii, axis_style);
+        scene.shapes(vec![Shape::Point(cell(axis_x, axis_y))], '+', DotMode::Ascii, axis_style);
+
+        // Y axis ticks, with labels right-aligned against the axis
+        let mut last_row: Option<u16> = None;
+        for tick in layout.y.ticks(self.y_label_width()) {
+            let tick_row: u16 = viewbox.translate_to_plot(Vec2::new(layout.x.bounds().0, tick.value)).y;
+            if last_row == Some(tick_row) { continue }
+            last_row = Some(tick_row);
+            scene.shapes(vec![Shape::Point(cell(axis_x, tick_row))], '+', DotMode::Ascii, axis_style);
+            let len: u16 = tick.label.chars().count() as u16;
+            scene.text(&tick.label, PVec2::new(axis_x.saturating_sub(len), tick_row), Style::PLAIN);
+        }
+
+        // X axis ticks, with labels centered below; labels that would overlap the previous one
+        // are skipped
+        let ticks: Vec<Tick> = layout.x.ticks(size.x as usize);
+        let spacing: usize = (size.x as usize / ticks.len().max(1)).max(2) - 1;
+        let mut next_free: u16 = 0;
+        for tick in ticks {
+            let label: String = match layout.x {
+                Axis::Category { .. } => clip(&tick.label, spacing),
+                _ => tick.label,
+            };
+            let tick_column: u16 = viewbox.translate_to_plot(Vec2::new(tick.value, layout.y.bounds().0)).x;
+            scene.shapes(vec![Shape::Point(cell(tick_column, axis_y))], '+', DotMode::Ascii, axis_style);
+            let start: u16 = self.centered(&label, tick_column);
+            if start < next_free { continue }
+            scene.text(&label, PVec2::new(start, axis_y + 1), Style::PLAIN);
+            next_free = start + label.chars().count() as u16 + 1;
         }
+        if !self.x.name().is_empty() {
+            scene.text(self.x.name(), PVec2::new(self.centered(self.x.name(), center), axis_y + 2), Style::PLAIN);
+        }
+    }
+
+    /// Add the legend to a scene, listing each series with its marker (as drawn in the chart's
+    /// mode) and color. In the automatic position, the legend goes in whichever corner of the data
+    /// area has the least drawn in and around it, so this should be run once the series have been
+    /// added to the scene.
+    pub fn legend(&self, scene: &mut Scene) {
+        let entries: Vec<Entry> = self.names.iter().enumerate()
+            .map(|(i, name)| Entry { name: name.clone(), symbol: self.mode.symbol(self.marker(i)), style: self.style(i) })
+            .collect();
+        let layout: Layout = self.layout();
+        match self.legend_position() {
+            LegendPosition::Auto => {
+                // leave a blank column between the legend and the rest of the data area
+                let size: PVec2 = column_size(&self.names) + PVec2::new(1, 0);
+                let Some(corner) = emptiest_corner(scene, (layout.position, layout.size), size) else { return };
+                let margin: u16 = (corner.x > layout.position.x) as u16;
+                column(scene, &entries, PVec2::new(corner.x + margin, corner.y));
+            }
+            LegendPosition::Top => {
+                let width: u16 = row_width(&self.names);
+                let center: u16 = layout.position.x + layout.size.x / 2;
+                let start: u16 = center.saturating_sub(width / 2).min(self.canvas.width().saturating_sub(width));
+                row(scene, &entries, PVec2::new(start, !self.title.is_empty() as u16));
+            }
+            LegendPosition::Right => column(scene, &entries, PVec2::new(layout.position.x + layout.size.x + 1, layout.position.y)),
+            LegendPosition::None => {}
+        }
+    }
+
+    /// Draw the title, both axes, their ticks and tick labels, and their names on the canvas.
+    #[cfg(test)]
+    pub fn draw_axes(&self) {
+        let mut scene: Scene = self.scene();
+        self.axes(&mut scene);
+        self.canvas.draw_scene(&scene);
+    }
+
+    /// Draw a scene on the canvas, and finish the chart.
+    pub fn draw(&self, scene: &Scene) {
+        self.canvas.draw_scene(scene);
+        self.finish();
+    }
+
+    /// Run this when you are done drawing the chart.
+    pub fn finish(&self) {
+        self.canvas.finish();
+    }
+}
+
+/// A kind of chart, drawn on top of a base chart.
+pub trait Chart {
+    /// Get the base chart.
+    fn base(&self) -> &BaseChart;
+
+    /// Get the base chart, to set the title and axis names.
+    fn base_mut(&mut self) -> &mut BaseChart;
+
+    /// Lay out the chart as a scene.
+    fn scene(&self) -> Scene;
+
+    /// Get the series of points drawn on the chart. Charts that aren't drawn from points have
+    /// none.
+    fn series(&self) -> &[Series] {
+        &[]
     }
-}
\ No newline at end of file
+
+    /// Draw the chart on its plot area.
+    fn draw(&self) {
+        self.base().draw(&self.scene());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::canvas::TextCanvas;
+    use crate::snapshot::assert_snapshot;
+
+    fn tick_values(axis: &Axis, max_width: usize) -> Vec<f32> {
+        axis.ticks(max_width).iter().map(|t| t.value).collect()
+    }
+
+    #[test]
+    fn nice_steps() {
+        assert_eq!((-3..=3).map(nice_step).collect::<Vec<f32>>(), vec![0.1, 0.2, 0.5, 1.0, 2.0, 5.0, 10.0]);
+        assert_eq!(nice_step(nice_step_index(3.0)), 5.0);
+        assert_eq!(nice_step(nice_step_index(0.05)), 0.05);
+        for at_least in [0.0, -2.0, f32::INFINITY, f32::NAN] {
+            assert_eq!(nice_step_index(at_least), 0);
+        }
+    }
+
+    #[test]
+    fn format_ticks() {
+        assert_eq!(format_tick(2.5, 1, 10), "2.5");
+        assert_eq!(format_tick(-0.0, 0, 10), "0");
+        assert_eq!(format_tick(1500000.0, 0, 5), "1.5M");
+        assert_eq!(format_tick(0.000012, 6, 5), "1.2e-5");
+        assert_eq!(format_number(7.50), "7.5");
+    }
+
+    #[test]
+    fn scaled_axis_resolves_to_nice_ticks() {
+        let axis: Axis = Axis::fit("", 3.0, 97.0).resolve(50, COLUMNS_PER_TICK, false);
+        let (min, max) = axis.bounds();
+        assert!((0.0..3.0).contains(&min), "padding should not cross zero");
+        assert!(max > 97.0);
+        assert_eq!(tick_values(&axis, 10), vec![0.0, 50.0, 100.0]);
+    }
+
+    #[test]
+    fn scaled_axis_marker_scale() {
+        let axis: Axis = Axis::fit("", 0.0, 100.0).with_marker_scale(1).resolve(50, COLUMNS_PER_TICK, false);
+        assert_eq!(tick_values(&axis, 10), vec![0.0, 20.0, 40.0, 60.0, 80.0, 100.0]);
+    }
+
+    #[test]
+    fn scaled_axis_spreads_out_wide_labels() {
+        let axis: Axis = Axis::fit("", 1e6, 9e6).resolve(20, 4.0, true);
+        let ticks: Vec<Tick> = axis.ticks(20);
+        let widest: usize = ticks.iter().map(|t| t.label.len()).max().unwrap();
+        assert!((widest + 1) * ticks.len() <= 20);
+    }
+
+    #[test]
+    fn log_axis_ticks_at_powers() {
+        let axis: Axis = Axis::fit_log("", Scale::Log10, 3.0, 20000.0).resolve(40, ROWS_PER_TICK, false);
+        let labels: Vec<String> = axis.ticks(10).into_iter().map(|t| t.label).collect();
+        assert_eq!(labels, vec!["10", "100", "1000", "10000"]);
+    }
+
+    #[test]
+    fn category_axis() {
+        let axis: Axis = Axis::Category { name: "".to_string(), labels: vec!["a".to_string(), "bcdef".to_string()] };
+        assert_eq!(axis.bounds(), (-0.5, 1.5));
+        assert_eq!(axis.ticks(3).into_iter().map(|t| t.label).collect::<Vec<String>>(), vec!["a", "bcd"]);
+        assert_eq!(axis.value_label(1.2), "bcdef");
+    }
+
+    #[test]
+    fn value_labels() {
+        assert_eq!(Axis::fit("", 0.0, 1.0).value_label(0.125), "0.125");
+        assert_eq!(Axis::fit_time("", 1700000000, 0.0, 60.0).value_label(30.0), "2023-11-14 22:13:50");
+    }
+
+    #[test]
+    fn axis_layout() {
+        let mut base: BaseChart = BaseChart::new(Box::new(TextCanvas::new(40, 12)));
+        base.set_x(Axis::fit("", 0.0, 10.0));
+        base.set_y(Axis::fit("", -1.0, 1.0));
+        base.set_title("Title");
+        base.set_names("x axis", "y axis");
+        let (position, size) = base.data_area();
+        // the title and Y axis name take the first two rows, and the Y tick labels ("-1") and
+        // axis line take the first three columns
+        assert_eq!(position, PVec2::new(3, 2));
+        assert_eq!(size, PVec2::new(37, 7));
+        base.draw_axes();
+        assert_snapshot("axis_layout", base.canvas());
+    }
+}
@@ -0,0 +1,243 @@
+use std::io::{self, Error, ErrorKind};
+use std::str::FromStr;
+use crate::chart::base::{Axis, BaseChart, Chart};
+use crate::data::Vec2;
+use crate::renderer::canvas::Canvas;
+use crate::renderer::dots::DotMode;
+use crate::renderer::scene::{Scene, Shape};
+
+/// Most bins that a histogram can have. Far more than a chart can show, but few enough that a
+/// tiny bin width, or an outlier far from the rest of the values, can't use up all the memory.
+const MAX_BINS: usize = 10_000;
+
+/// Method for splitting values into bins.
+#[derive(PartialEq, Copy, Clone)]
+pub enum Binning {
+    /// A fixed number of bins, spread evenly over the range of the values.
+    Count(usize),
+    /// Bins of a fixed width, aligned to multiples of the width.
+    Width(f32),
+    /// Sturges' rule: `log2(n) + 1` bins.
+    Sturges,
+    /// Freedman–Diaconis rule: bins of width `2 * IQR / cbrt(n)`.
+    FreedmanDiaconis,
+}
+
+impl FromStr for Binning {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sturges" => Ok(Binning::Sturges),
+            "fd" | "freedman-diaconis" => Ok(Binning::FreedmanDiaconis),
+            _ => Err(format!("unknown binning method \"{}\" (expected \"sturges\" or \"fd\")", s)),
+        }
+    }
+}
+
+/// A range of values, and how many values fall within it.
+#[derive(PartialEq, Copy, Clone)]
+pub struct Bin {
+    pub start: f32,
+    pub end: f32,
+    pub count: usize,
+}
+
+/// Get a quantile (0.0 - 1.0) of a sorted list of values, interpolating between values.
+fn quantile(sorted: &[f32], q: f32) -> f32 {
+    let pos: f32 = q * (sorted.len() - 1) as f32;
+    let lower: usize = pos.floor() as usize;
+    let upper: usize = pos.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f32)
+}
+
+/// Split a list of values into bins. Fails if no bins are asked for, or if the bins asked for
+/// would be more than `MAX_BINS`; with the Freedman–Diaconis rule, the bins are widened instead.
+pub fn bin(values: &[f32], binning: Binning) -> io::Result<Vec<Bin>> {
+    if binning == Binning::Count(0) {
+        return Err(Error::new(ErrorKind::InvalidInput, "number of bins must be greater than zero"));
+    }
+    if values.is_empty() { return Ok(Vec::new()) }
+    let mut sorted: Vec<f32> = values.to_vec();
+    sorted.sort_by(f32::total_cmp);
+    let min: f32 = sorted[0];
+    let max: f32 = sorted[sorted.len() - 1];
+    if min == max {
+        return Ok(vec![Bin { start: min - 0.5, end: max + 0.5, count: values.len() }]);
+    }
+    let sturges: usize = (sorted.len() as f32).log2().ceil() as usize + 1;
+    // get the start of the first bin, the bin width, and the number of bins
+    let (start, width, count) = match binning {
+        Binning::Count(count) if count > MAX_BINS => {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("too many bins: {} (at most {} are allowed)", count, MAX_BINS)));
+        }
+        Binning::Count(count) => (min, (max - min) / count as f32, count),
+        Binning::Width(width) => {
+            let start: f32 = (min / width).floor() * width;
+            // count in f64 first, since the count could be too big for any integer
+            let count: f64 = ((max - start) as f64 / width as f64).floor() + 1.0;
+            if count.is_nan() || count > MAX_BINS as f64 {
+                return Err(Error::new(ErrorKind::InvalidInput, format!(
+                    "a bin width of {} would need {:.0} bins for values from {} to {} (at most {} are allowed)",
+                    width, count, min, max, MAX_BINS,
+                )));
+            }
+            (start, width, (count as usize).max(1))
+        }
+        Binning::Sturges => (min, (max - min) / sturges as f32, sturges),
+        Binning::FreedmanDiaconis => {
+            let iqr: f32 = quantile(&sorted, 0.75) - quantile(&sorted, 0.25);
+            let width: f32 = 2.0 * iqr / (sorted.len() as f32).cbrt();
+            if width > 0.0 {
+                let count: f32 = ((max - min) / width).ceil().max(1.0);
+                // outliers far from the rest of the values would need too many bins
+                if count <= MAX_BINS as f32 {
+                    (min, width, count as usize)
+                } else {
+                    (min, (max - min) / MAX_BINS as f32, MAX_BINS)
+                }
+            } else {
+                (min, (max - min) / sturges as f32, sturges)
+            }
+        }
+    };
+    let mut bins: Vec<Bin> = (0..count).map(|i| Bin {
+        start: start + width * i as f32,
+        end: start + width * (i + 1) as f32,
+        count: 0,
+    }).collect();
+    for value in sorted {
+        // the maximum value would fall just outside the last bin, so keep it in the last one
+        let index: usize = (((value - start) / width).floor().max(0.0) as usize).min(count - 1);
+        bins[index].count += 1;
+    }
+    Ok(bins)
+}
+
+/// Histogram. Splits a list of values into bins, and plots the number of values in each bin as a
+/// bar.
+pub struct HistChart {
+    base: BaseChart,
+    bins: Vec<Bin>,
+    symbol: char,
+}
+
+impl HistChart {
+    /// Create a new histogram from a list of values. Fails if there would be too many bins (see
+    /// `bin()`).
+    pub fn new(canvas: Box<dyn Canvas>, values: &[f32], binning: Binning) -> io::Result<HistChart> {
+        let mut base: BaseChart = BaseChart::new(canvas);
+        let bins: Vec<Bin> = bin(values, binning)?;
+        if let (Some(first), Some(last)) = (bins.first(), bins.last()) {
+            let max_count: usize = bins.iter().map(|b| b.count).max().unwrap_or(0);
+            base.set_x(Axis::fit("", first.start, last.end));
+            base.set_y(Axis::fit("", 0.0, max_count as f32));
+        }
+        Ok(HistChart { base, bins, symbol: '#' })
+    }
+
+    /// Set how bars are drawn. In modes other than `DotMode::Ascii`, bars are filled with dots,
+    /// so their ends don't have to line up with whole cells.
+    pub fn dots(mut self, mode: DotMode) -> HistChart {
+        self.base.set_mode(mode);
+        self
+    }
+}
+
+impl Chart for HistChart {
+    fn base(&self) -> &BaseChart {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut BaseChart {
+        &mut self.base
+    }
+
+    /// Lay out the chart as a scene.
+    fn scene(&self) -> Scene {
+        let mut scene: Scene = self.base.scene();
+        self.base.axes(&mut scene);
+        let viewbox = self.base.viewbox();
+        // empty bins are skipped, since a filled rectangle always takes up at least one row
+        let bars: Vec<Shape> = self.bins.iter().filter(|b| b.count > 0)
+            .map(|bin| Shape::Rect(viewbox.translate(Vec2::new(bin.start, 0.0)), viewbox.translate(Vec2::new(bin.end, bin.count as f32))))
+            .collect();
+        scene.shapes(bars, self.symbol, self.base.mode(), self.base.style(0));
+        scene
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{ColumnRef, Table};
+    use crate::snapshot::{assert_chart, canvas, fixture};
+
+    fn samples() -> Vec<f32> {
+        fixture("samples.txt", "float").numbers(&ColumnRef::Index(0)).unwrap()
+    }
+
+    #[test]
+    fn bins_cover_every_value() {
+        let values: Vec<f32> = samples();
+        for binning in [Binning::Count(7), Binning::Width(5.0), Binning::Sturges, Binning::FreedmanDiaconis] {
+            let bins: Vec<Bin> = bin(&values, binning).unwrap();
+            assert_eq!(bins.iter().map(|b| b.count).sum::<usize>(), values.len());
+            assert!(bins.windows(2).all(|pair| (pair[0].end - pair[1].start).abs() < 1e-3));
+        }
+    }
+
+    #[test]
+    fn bins_of_fixed_width_are_aligned() {
+        let bins: Vec<Bin> = bin(&[1.0, 7.0, 12.0], Binning::Width(5.0)).unwrap();
+        assert_eq!(bins.iter().map(|b| (b.start, b.count)).collect::<Vec<(f32, usize)>>(), vec![(0.0, 1), (5.0, 1), (10.0, 1)]);
+    }
+
+    #[test]
+    fn zero_bins_are_rejected() {
+        assert!(bin(&samples(), Binning::Count(0)).is_err());
+        assert!(bin(&[1.0, 1.0], Binning::Count(0)).is_err());
+    }
+
+    #[test]
+    fn tiny_bin_widths_are_rejected() {
+        let values: Vec<f32> = (1..=1000000).map(|i| i as f32).collect();
+        assert!(bin(&values, Binning::Width(0.0001)).is_err());
+        assert!(bin(&values, Binning::Count(MAX_BINS + 1)).is_err());
+    }
+
+    #[test]
+    fn outliers_limit_freedman_diaconis_bins() {
+        let mut values: Vec<f32> = (0..2000).map(|i| 12.0 + (i % 100) as f32 / 100.0).collect();
+        values.push(3e9);
+        let bins: Vec<Bin> = bin(&values, Binning::FreedmanDiaconis).unwrap();
+        assert_eq!(bins.len(), MAX_BINS);
+        assert_eq!(bins.iter().map(|b| b.count).sum::<usize>(), values.len());
+        assert_eq!(bins[MAX_BINS - 1].count, 1);
+    }
+
+    #[test]
+    fn hist_chart() {
+        let table: Table = fixture("samples.txt", "float");
+        let values: Vec<f32> = table.numbers(&ColumnRef::Index(0)).unwrap();
+        let mut chart: HistChart = HistChart::new(canvas(60, 18), &values, Binning::Sturges).unwrap();
+        chart.base_mut().set_names("value", "count");
+        let lines: Vec<String> = assert_chart("hist_chart", &chart);
+        // the tallest bin has more than 15 values, so its bar reaches above the 15 tick
+        assert!(bin(&values, Binning::Sturges).unwrap().iter().any(|b| b.count > 15));
+        let tallest: usize = lines.iter().position(|l| l.contains('#')).unwrap();
+        assert!(lines[tallest + 1].starts_with("15+"));
+    }
+
+    #[test]
+    fn hist_chart_half_blocks() {
+        let mut chart: HistChart = HistChart::new(canvas(60, 18), &samples(), Binning::Sturges).unwrap()
+            .dots(DotMode::HalfBlock);
+        chart.base_mut().set_names("value", "count");
+        let lines: Vec<String> = assert_chart("hist_chart_half_blocks", &chart);
+        // bars start and end at the centers of cells, so only half of those cells are filled
+        let zero: &String = lines.iter().find(|l| l.starts_with(" 0+")).unwrap();
+        assert!(zero[3..].trim().chars().all(|c| c == '\u{2580}'));
+        assert!(lines.iter().any(|l| l.contains("\u{2588}\u{2584}")));
+    }
+}
@@ -0,0 +1,176 @@
+/// Legends, which list each series of a chart with the symbol and color it is drawn with.
+use std::str::FromStr;
+use crate::data::{PVec2, Vec2};
+use crate::renderer::canvas::{Canvas, TextCanvas};
+use crate::renderer::color::Style;
+use crate::renderer::dots::DotMode;
+use crate::renderer::scene::{Scene, Shape};
+
+/// Number of spaces between entries in a row.
+const ROW_GAP: u16 = 2;
+
+/// Where to put the legend of a chart.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum LegendPosition {
+    /// Inside the data area, in whichever corner has the least drawn in it. Only charts with more
+    /// than one series get a legend.
+    Auto,
+    /// In a row above the data area, below the title.
+    Top,
+    /// In a column to the right of the data area.
+    Right,
+    /// No legend.
+    None,
+}
+
+impl FromStr for LegendPosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(LegendPosition::Auto),
+            "top" => Ok(LegendPosition::Top),
+            "right" => Ok(LegendPosition::Right),
+            "none" => Ok(LegendPosition::None),
+            _ => Err(format!("unknown legend position \"{}\" (expected auto, top, right or none)", s)),
+        }
+    }
+}
+
+/// A series listed in a legend.
+pub struct Entry {
+    pub name: String,
+    /// Character that the series is drawn with.
+    pub symbol: char,
+    pub style: Style,
+}
+
+/// Get the width of an entry: its symbol, a space and its name.
+fn entry_width(name: &str) -> u16 {
+    name.chars().count() as u16 + 2
+}
+
+/// Add the symbol of an entry to a scene, at a cell. The symbol is added as a point rather than as
+/// text, so that image back ends draw it as a marker, like the points of a series, instead of
+/// looking it up in a font.
+fn symbol(scene: &mut Scene, entry: &Entry, cell: PVec2) {
+    let point: Shape = Shape::Point(Vec2::new(cell.x as f32, cell.y as f32));
+    scene.shapes(vec![point], entry.symbol, DotMode::Ascii, entry.style);
+}
+
+/// Get the size of a legend drawn as a column, with one entry per row.
+pub fn column_size(names: &[String]) -> PVec2 {
+    PVec2::new(names.iter().map(|n| entry_width(n)).max().unwrap_or(0), names.len() as u16)
+}
+
+/// Get the width of a legend drawn as a row.
+pub fn row_width(names: &[String]) -> u16 {
+    let widths: u16 = names.iter().map(|n| entry_width(n)).sum();
+    widths + ROW_GAP * names.len().saturating_sub(1) as u16
+}
+
+/// Add a legend to a scene as a column of entries, starting at a cell. Names are padded to the
+/// same width, so that the legend covers anything drawn below it.
+pub fn column(scene: &mut Scene, entries: &[Entry], position: PVec2) {
+    let width: usize = entries.iter().map(|e| e.name.chars().count()).max().unwrap_or(0);
+    for (i, entry) in entries.iter().enumerate() {
+        let row: u16 = position.y + i as u16;
+        symbol(scene, entry, PVec2::new(position.x, row));
+        scene.text(&format!(" {:<width$}", entry.name, width = width), PVec2::new(position.x + 1, row), Style::PLAIN);
+    }
+}
+
+/// Add a legend to a scene as a row of entries, starting at a cell.
+pub fn row(scene: &mut Scene, entries: &[Entry], position: PVec2) {
+    let mut x: u16 = position.x;
+    for entry in entries {
+        symbol(scene, entry, PVec2::new(x, position.y));
+        scene.text(&format!(" {}", entry.name), PVec2::new(x + 1, position.y), Style::PLAIN);
+        x += entry_width(&entry.name) + ROW_GAP;
+    }
+}
+
+/// Find the corner of an area (given by its position and size) where a box of a certain size
+/// covers the fewest cells that have been drawn on in a scene, or `None` if the box doesn't fit
+/// in the area. Between corners where the box covers as many cells, the one with the fewest
+/// drawn cells next to the box (inside the area) is used, so that the box doesn't run into
+/// anything. Corners are tried clockwise from the top right, and the first of any that tie is
+/// used.
+pub fn emptiest_corner(scene: &Scene, area: (PVec2, PVec2), size: PVec2) -> Option<PVec2> {
+    let (position, area_size) = area;
+    if size.x > area_size.x || size.y > area_size.y { return None }
+    let canvas: TextCanvas = TextCanvas::new(scene.width, scene.height);
+    scene.draw(&canvas);
+    let (left, top) = (position.x, position.y);
+    let (right, bottom) = (position.x + area_size.x - size.x, position.y + area_size.y - size.y);
+    let corners: [PVec2; 4] = [
+        PVec2::new(right, top),
+        PVec2::new(right, bottom),
+        PVec2::new(left, bottom),
+        PVec2::new(left, top),
+    ];
+    // count the drawn cells between two opposite corners (the second one is left out)
+    let used = |from: PVec2, to: PVec2| (from.y..to.y)
+        .flat_map(|y| (from.x..to.x).map(move |x| PVec2::new(x, y)))
+        .filter(|cell| canvas.get(cell) != ' ')
+        .count();
+    let (area_right, area_bottom) = (position.x + area_size.x, position.y + area_size.y);
+    corners.into_iter().min_by_key(|corner| {
+        let covered: usize = used(*corner, *corner + size);
+        let around_from: PVec2 = PVec2::new(corner.x.saturating_sub(1).max(left), corner.y.saturating_sub(1).max(top));
+        let around_to: PVec2 = PVec2::new((corner.x + size.x + 1).min(area_right), (corner.y + size.y + 1).min(area_bottom));
+        (covered, used(around_from, around_to) - covered)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::scene::Element;
+
+    #[test]
+    fn legend_positions() {
+        assert_eq!("top".parse::<LegendPosition>(), Ok(LegendPosition::Top));
+        assert_eq!("none".parse::<LegendPosition>(), Ok(LegendPosition::None));
+        assert!("left".parse::<LegendPosition>().is_err());
+    }
+
+    #[test]
+    fn legend_sizes() {
+        let names: Vec<String> = vec!["p50".to_string(), "p99.9".to_string()];
+        assert_eq!(column_size(&names), PVec2::new(7, 2));
+        assert_eq!(row_width(&names), 5 + 2 + 7);
+    }
+
+    #[test]
+    fn legend_symbols_are_points() {
+        let entries: Vec<Entry> = vec![Entry { name: "p50".to_string(), symbol: '\u{28ff}', style: Style::PLAIN }];
+        let mut scene: Scene = Scene::new(10, 2);
+        row(&mut scene, &entries, PVec2::new(2, 1));
+        let point: Shape = Shape::Point(Vec2::new(2.0, 1.0));
+        assert_eq!(scene.elements()[0], Element::Shapes { shapes: vec![point], symbol: '\u{28ff}', mode: DotMode::Ascii, style: Style::PLAIN });
+        let canvas: TextCanvas = TextCanvas::new(10, 2);
+        scene.draw(&canvas);
+        assert_eq!(canvas.lines()[1], "  \u{28ff} p50   ");
+    }
+
+    #[test]
+    fn legends_go_in_the_emptiest_corner() {
+        let mut scene: Scene = Scene::new(20, 10);
+        // a line along the top, and down the right side
+        scene.shapes(vec![Shape::Line(Vec2::new(0.0, 0.0), Vec2::new(19.0, 0.0))], '*', DotMode::Ascii, Style::PLAIN);
+        scene.shapes(vec![Shape::Line(Vec2::new(19.0, 0.0), Vec2::new(19.0, 9.0))], '*', DotMode::Ascii, Style::PLAIN);
+        let area: (PVec2, PVec2) = (PVec2::new(0, 0), PVec2::new(20, 10));
+        assert_eq!(emptiest_corner(&scene, area, PVec2::new(5, 2)), Some(PVec2::new(0, 8)));
+        assert_eq!(emptiest_corner(&scene, area, PVec2::new(25, 2)), None);
+    }
+
+    #[test]
+    fn legends_keep_away_from_nearby_cells() {
+        let mut scene: Scene = Scene::new(20, 10);
+        // a point just below where a legend would go in the top right corner
+        scene.shapes(vec![Shape::Point(Vec2::new(17.0, 2.0))], '*', DotMode::Ascii, Style::PLAIN);
+        let area: (PVec2, PVec2) = (PVec2::new(0, 0), PVec2::new(20, 10));
+        assert_eq!(emptiest_corner(&scene, area, PVec2::new(5, 2)), Some(PVec2::new(15, 8)));
+    }
+}
@@ -1,4 +1,172 @@
-/// Line chart.
+use crate::chart::base::{BaseChart, Chart};
+use crate::data::Series;
+use crate::renderer::canvas::Canvas;
+use crate::renderer::dots::DotMode;
+use crate::renderer::scene::{Scene, Shape};
+use crate::renderer::shapes::{Scale, ScaledViewBox};
+
+/// Line chart. Plots one or more series of points, joined together by lines.
 pub struct LineChart {
-    
-}
\ No newline at end of file
+    base: BaseChart,
+    series: Vec<Series>,
+}
+
+impl LineChart {
+    /// Create a new line chart. The axes are fit to the points in all series.
+    pub fn new(canvas: Box<dyn Canvas>, series: Vec<Series>) -> LineChart {
+        let mut base: BaseChart = BaseChart::new(canvas);
+        base.fit_series(&series);
+        LineChart { base, series }
+    }
+
+    /// Set the markers to use for each series (see `BaseChart::set_markers`).
+    pub fn markers(mut self, markers: &str) -> LineChart {
+        self.base.set_markers(markers);
+        self
+    }
+
+    /// Set how lines are drawn. In modes other than `DotMode::Ascii`, each series is drawn with
+    /// dots instead of its marker.
+    pub fn dots(mut self, mode: DotMode) -> LineChart {
+        self.base.set_mode(mode);
+        self
+    }
+
+    /// Set the scales of the X and Y axes, and fit the axes to the points again (see
+    /// `BaseChart::fit_scales`).
+    pub fn scales(mut self, x: Scale, y: Scale) -> LineChart {
+        self.base.fit_scales(x, y, self.series.iter().flat_map(|s| s.points.iter()));
+        self
+    }
+
+    /// Use a time axis for X, and fit the axes to the points again (see `BaseChart::fit_time`).
+    pub fn time(mut self, origin: i64) -> LineChart {
+        self.base.fit_time(origin, self.series.iter().flat_map(|s| s.points.iter()));
+        self
+    }
+
+    /// Replace the series, and fit the axes to their points.
+    pub fn set_series(&mut self, series: Vec<Series>) {
+        self.base.fit_series(&series);
+        self.series = series;
+    }
+
+    /// Get the shapes that make up a series: lines between each pair of points, or a point if
+    /// there is only one. Lines are cut off at the edges of the axes.
+    fn shapes(&self, viewbox: &ScaledViewBox, series: &Series) -> Vec<Shape> {
+        let mut shapes: Vec<Shape> = Vec::new();
+        if let [only] = series.points.as_slice() {
+            if self.base.can_show(only) && viewbox.contains(*only) { shapes.push(Shape::Point(viewbox.translate(*only))); }
+        }
+        for pair in series.points.windows(2) {
+            if !self.base.can_show(&pair[0]) || !self.base.can_show(&pair[1]) { continue }
+            let Some((start, end)) = viewbox.clip_line(pair[0], pair[1]) else { continue };
+            shapes.push(Shape::Line(viewbox.translate(start), viewbox.translate(end)));
+        }
+        shapes
+    }
+}
+
+impl Chart for LineChart {
+    fn base(&self) -> &BaseChart {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut BaseChart {
+        &mut self.base
+    }
+
+    fn series(&self) -> &[Series] {
+        &self.series
+    }
+
+    /// Lay out the chart as a scene.
+    fn scene(&self) -> Scene {
+        let mut scene: Scene = self.base.scene();
+        self.base.axes(&mut scene);
+        let viewbox = self.base.viewbox();
+        for (i, series) in self.series.iter().enumerate() {
+            scene.shapes(self.shapes(&viewbox, series), self.base.marker(i), self.base.mode(), self.base.style(i));
+        }
+        self.base.legend(&mut scene);
+        scene
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::legend::LegendPosition;
+    use crate::data::{ColumnRef, PVec2, Table, TimeFormat, Vec2};
+    use crate::renderer::color::{Style, PALETTE};
+    use crate::snapshot::{assert_chart, canvas, fixture};
+
+    #[test]
+    fn line_chart() {
+        let table: Table = fixture("latency.csv", "csv");
+        let y: Vec<ColumnRef> = vec![ColumnRef::Name("p50".to_string()), ColumnRef::Name("p99".to_string())];
+        let series: Vec<Series> = table.series(Some(&ColumnRef::Name("time".to_string())), &y).unwrap();
+        let mut chart: LineChart = LineChart::new(canvas(60, 20), series).markers("*o");
+        chart.base_mut().set_title("Latency");
+        chart.base_mut().set_names("time", "ms");
+        let lines: Vec<String> = assert_chart("line_chart", &chart);
+        // each series is drawn in its own color from the palette
+        let row: usize = lines.iter().position(|l| l.starts_with("50+")).unwrap();
+        let p99: PVec2 = PVec2::new(lines[row].find('o').unwrap() as u16, row as u16);
+        let row: usize = lines.iter().position(|l| l.starts_with("10+")).unwrap();
+        let p50: PVec2 = PVec2::new(lines[row].find('*').unwrap() as u16, row as u16);
+        assert_eq!(chart.base().canvas().style(&p50), Style::color(PALETTE[0]));
+        assert_eq!(chart.base().canvas().style(&p99), Style::color(PALETTE[1]));
+    }
+
+    #[test]
+    fn line_chart_legend_on_the_right() {
+        let rising: Vec<Vec2> = (0..10).map(|i| Vec2::new(i as f32, i as f32)).collect();
+        let falling: Vec<Vec2> = (0..10).map(|i| Vec2::new(i as f32, 9.0 - i as f32)).collect();
+        let series: Vec<Series> = vec![Series::new("rising", rising), Series::new("falling", falling)];
+        let mut chart: LineChart = LineChart::new(canvas(40, 12), series).markers("/\\");
+        chart.base_mut().set_legend(LegendPosition::Right);
+        let lines: Vec<String> = assert_chart("line_chart_legend_on_the_right", &chart);
+        assert!(lines[0].trim_end().ends_with("/ rising"));
+        assert!(lines[1].trim_end().ends_with("\\ falling"));
+    }
+
+    #[test]
+    fn line_chart_braille() {
+        let points: Vec<Vec2> = (0..60).map(|i| Vec2::new(i as f32, (i as f32 / 6.0).sin())).collect();
+        let series: Vec<Series> = vec![Series::new("sine", points)];
+        let chart: LineChart = LineChart::new(canvas(40, 12), series).dots(DotMode::Braille);
+        let lines: Vec<String> = assert_chart("line_chart_braille", &chart);
+        // the line is drawn only with braille, with several dots packed into some cells
+        let cells: Vec<char> = lines.iter().flat_map(|l| l.chars().skip(3)).filter(|c| !c.is_ascii()).collect();
+        assert!(cells.iter().all(|c| ('\u{2800}'..='\u{28ff}').contains(c)));
+        assert!(cells.iter().any(|c| (*c as u32 - 0x2800).count_ones() >= 3));
+        assert!(!lines.iter().any(|l| l.contains('*')));
+    }
+
+    #[test]
+    fn line_chart_log_scale() {
+        let points: Vec<Vec2> = (0..20).map(|i| Vec2::new(i as f32, 2f32.powi(i))).collect();
+        let series: Vec<Series> = vec![Series::new("powers", points)];
+        let chart: LineChart = LineChart::new(canvas(50, 16), series)
+            .scales(Scale::Linear, Scale::Log10);
+        let lines: Vec<String> = assert_chart("line_chart_log_scale", &chart);
+        let ticks: Vec<&str> = lines.iter().filter_map(|l| l.split_once('+')).map(|(label, _)| label.trim()).collect();
+        assert_eq!(ticks, vec!["1000000", "10000", "100", "1", ""]);
+    }
+
+    #[test]
+    fn line_chart_time_axis() {
+        let table: Table = fixture("metrics.csv", "csv");
+        let (origin, series) = table.time_series(
+            &ColumnRef::Name("time".to_string()),
+            &TimeFormat::Auto,
+            &[ColumnRef::Name("requests".to_string())],
+        ).unwrap();
+        let chart: LineChart = LineChart::new(canvas(60, 16), series).time(origin);
+        let lines: Vec<String> = assert_chart("line_chart_time_axis", &chart);
+        // ticks fall on whole hours
+        let labels: Vec<&str> = lines[lines.len() - 1].split_whitespace().collect();
+        assert_eq!(labels, vec!["09:00", "12:00", "15:00", "18:00"]);
+    }
+}
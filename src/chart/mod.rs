@@ -1,3 +1,8 @@
 /// High-level chart drawing.
-mod base;
-pub mod line;
\ No newline at end of file
+pub mod base;
+pub mod bar;
+pub mod hist;
+pub mod legend;
+pub mod line;
+pub mod scatter;
+pub mod time;
\ No newline at end of file
@@ -0,0 +1,97 @@
+use crate::chart::base::{BaseChart, Chart};
+use crate::data::Series;
+use crate::renderer::canvas::Canvas;
+use crate::renderer::dots::DotMode;
+use crate::renderer::scene::{Scene, Shape};
+use crate::renderer::shapes::Scale;
+
+/// Scatter chart. Plots one or more series of points, each with its own marker.
+pub struct ScatterChart {
+    base: BaseChart,
+    series: Vec<Series>,
+}
+
+impl ScatterChart {
+    /// Create a new scatter chart. The axes are fit to the points in all series.
+    pub fn new(canvas: Box<dyn Canvas>, series: Vec<Series>) -> ScatterChart {
+        let mut base: BaseChart = BaseChart::new(canvas);
+        base.fit_series(&series);
+        ScatterChart { base, series }
+    }
+
+    /// Set the markers to use for each series (see `BaseChart::set_markers`).
+    pub fn markers(mut self, markers: &str) -> ScatterChart {
+        self.base.set_markers(markers);
+        self
+    }
+
+    /// Set how points are drawn. In modes other than `DotMode::Ascii`, each point is a single
+    /// dot instead of a marker.
+    pub fn dots(mut self, mode: DotMode) -> ScatterChart {
+        self.base.set_mode(mode);
+        self
+    }
+
+    /// Set the scales of the X and Y axes, and fit the axes to the points again (see
+    /// `BaseChart::fit_scales`).
+    pub fn scales(mut self, x: Scale, y: Scale) -> ScatterChart {
+        self.base.fit_scales(x, y, self.series.iter().flat_map(|s| s.points.iter()));
+        self
+    }
+
+    /// Use a time axis for X, and fit the axes to the points again (see `BaseChart::fit_time`).
+    pub fn time(mut self, origin: i64) -> ScatterChart {
+        self.base.fit_time(origin, self.series.iter().flat_map(|s| s.points.iter()));
+        self
+    }
+}
+
+impl Chart for ScatterChart {
+    fn base(&self) -> &BaseChart {
+        &self.base
+    }
+
+    fn base_mut(&mut self) -> &mut BaseChart {
+        &mut self.base
+    }
+
+    fn series(&self) -> &[Series] {
+        &self.series
+    }
+
+    /// Lay out the chart as a scene.
+    fn scene(&self) -> Scene {
+        let mut scene: Scene = self.base.scene();
+        self.base.axes(&mut scene);
+        let viewbox = self.base.viewbox();
+        for (i, series) in self.series.iter().enumerate() {
+            let points: Vec<Shape> = series.points.iter()
+                .filter(|p| self.base.can_show(p) && viewbox.contains(**p))
+                .map(|p| Shape::Point(viewbox.translate(*p)))
+                .collect();
+            scene.shapes(points, self.base.marker(i), self.base.mode(), self.base.style(i));
+        }
+        self.base.legend(&mut scene);
+        scene
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{ColumnRef, Table};
+    use crate::snapshot::{assert_chart, canvas, fixture};
+
+    #[test]
+    fn scatter_chart() {
+        let table: Table = fixture("points.txt", "whitespace");
+        let series: Vec<Series> = table.series(Some(&ColumnRef::Index(0)), &[ColumnRef::Index(1), ColumnRef::Index(2)]).unwrap();
+        let chart: ScatterChart = ScatterChart::new(canvas(60, 20), series).markers("ox");
+        let lines: Vec<String> = assert_chart("scatter_chart", &chart);
+        // both series start at (0, 0), where the later one is drawn on top
+        assert!(lines.iter().any(|l| l.starts_with("  0+x")));
+        // the bottom left is the only corner with room for the legend
+        assert!(lines[16].starts_with("-10+o column 2"));
+        assert!(lines[17].starts_with("   |x column 3"));
+    }
+}
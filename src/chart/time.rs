@@ -0,0 +1,218 @@
+/// Calendar-aware tick spacing for time axes. Times are in seconds since the Unix epoch, and
+/// calendar boundaries are in UTC.
+use chrono::{DateTime, Datelike, NaiveDate, Timelike, Utc};
+
+const MINUTE: f64 = 60.0;
+const HOUR: f64 = 60.0 * MINUTE;
+const DAY: f64 = 24.0 * HOUR;
+
+/// The Unix epoch was a Thursday; weeks start four days later, on Monday.
+const FIRST_MONDAY: f64 = 4.0 * DAY;
+
+/// Most boundaries that will be listed at once, so that a bad step can't create an endless
+/// number of ticks.
+const MAX_BOUNDARIES: usize = 1000;
+
+/// Unit of calendar time that ticks can be spaced by.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeUnit {
+    /// Get the length of this unit in seconds. Months and years vary in length, so an average
+    /// is used.
+    fn seconds(&self) -> f64 {
+        match self {
+            TimeUnit::Second => 1.0,
+            TimeUnit::Minute => MINUTE,
+            TimeUnit::Hour => HOUR,
+            TimeUnit::Day => DAY,
+            TimeUnit::Week => 7.0 * DAY,
+            TimeUnit::Month => 30.44 * DAY,
+            TimeUnit::Year => 365.25 * DAY,
+        }
+    }
+}
+
+/// Spacing between ticks on a time axis: some number of a calendar unit.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct TimeStep {
+    pub unit: TimeUnit,
+    pub count: u32,
+}
+
+/// Step sizes that line up with the calendar, from smallest to largest. Steps larger than these
+/// are 10, 20, 50, 100... years.
+const STEPS: &[TimeStep] = &[
+    TimeStep { unit: TimeUnit::Second, count: 1 },
+    TimeStep { unit: TimeUnit::Second, count: 2 },
+    TimeStep { unit: TimeUnit::Second, count: 5 },
+    TimeStep { unit: TimeUnit::Second, count: 10 },
+    TimeStep { unit: TimeUnit::Second, count: 15 },
+    TimeStep { unit: TimeUnit::Second, count: 30 },
+    TimeStep { unit: TimeUnit::Minute, count: 1 },
+    TimeStep { unit: TimeUnit::Minute, count: 2 },
+    TimeStep { unit: TimeUnit::Minute, count: 5 },
+    TimeStep { unit: TimeUnit::Minute, count: 10 },
+    TimeStep { unit: TimeUnit::Minute, count: 15 },
+    TimeStep { unit: TimeUnit::Minute, count: 30 },
+    TimeStep { unit: TimeUnit::Hour, count: 1 },
+    TimeStep { unit: TimeUnit::Hour, count: 2 },
+    TimeStep { unit: TimeUnit::Hour, count: 3 },
+    TimeStep { unit: TimeUnit::Hour, count: 6 },
+    TimeStep { unit: TimeUnit::Hour, count: 12 },
+    TimeStep { unit: TimeUnit::Day, count: 1 },
+    TimeStep { unit: TimeUnit::Day, count: 2 },
+    TimeStep { unit: TimeUnit::Week, count: 1 },
+    TimeStep { unit: TimeUnit::Month, count: 1 },
+    TimeStep { unit: TimeUnit::Month, count: 3 },
+    TimeStep { unit: TimeUnit::Month, count: 6 },
+    TimeStep { unit: TimeUnit::Year, count: 1 },
+    TimeStep { unit: TimeUnit::Year, count: 2 },
+    TimeStep { unit: TimeUnit::Year, count: 5 },
+];
+
+/// Get the date and time at a number of seconds since the Unix epoch.
+fn datetime(time: f64) -> DateTime<Utc> {
+    DateTime::from_timestamp(time.floor() as i64, 0).unwrap_or_default()
+}
+
+/// Get the full date and time at a number of seconds since the Unix epoch, down to the second.
+pub fn timestamp(time: f64) -> String {
+    datetime(time).format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Get the number of seconds since the Unix epoch at the start of a month. Months are counted
+/// from January of year 0.
+fn month_start(months: i64) -> f64 {
+    NaiveDate::from_ymd_opt(months.div_euclid(12) as i32, months.rem_euclid(12) as u32 + 1, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc().timestamp() as f64)
+        .unwrap_or(f64::INFINITY)
+}
+
+impl TimeStep {
+    /// Get the step at a position in the list of step sizes, from smallest to largest.
+    pub fn nth(index: usize) -> TimeStep {
+        match STEPS.get(index) {
+            Some(step) => *step,
+            None => {
+                // 1, 2 or 5 times a power of 10 years
+                let k: usize = index - STEPS.len() + 3;
+                let count: u32 = [1u32, 2, 5][k % 3].saturating_mul(10u32.saturating_pow(k as u32 / 3));
+                TimeStep { unit: TimeUnit::Year, count }
+            }
+        }
+    }
+
+    /// Find the position of the smallest step that is at least as long as a number of seconds. The
+    /// largest step is used if nothing is long enough.
+    pub fn index_at_least(seconds: f64) -> usize {
+        (0..).find(|i| Self::nth(*i).seconds() >= seconds || Self::nth(*i).count == u32::MAX).unwrap_or(0)
+    }
+
+    /// Get the length of this step in seconds (roughly, for months and years).
+    pub fn seconds(&self) -> f64 {
+        self.unit.seconds() * self.count as f64
+    }
+
+    /// Get every time on a boundary of this step within a range.
+    pub fn boundaries(&self, min: f64, max: f64) -> Vec<f64> {
+        let months: Option<i64> = match self.unit {
+            TimeUnit::Month => Some(self.count as i64),
+            TimeUnit::Year => Some(self.count as i64 * 12),
+            _ => None,
+        };
+        match months {
+            Some(months) => {
+                // count months from year 0, so that multi-year steps land on round years
+                let start: DateTime<Utc> = datetime(min);
+                let first: i64 = start.year() as i64 * 12 + start.month0() as i64;
+                let first: i64 = first.div_euclid(months) * months;
+                (0..).map(|i| month_start(first + i * months))
+                    .skip_while(|t| *t < min)
+                    .take_while(|t| *t <= max)
+                    .take(MAX_BOUNDARIES)
+                    .collect()
+            }
+            None => {
+                let size: f64 = self.seconds();
+                let offset: f64 = if self.unit == TimeUnit::Week { FIRST_MONDAY } else { 0.0 };
+                let first: f64 = ((min - offset) / size).ceil() * size + offset;
+                (0..).map(|i| first + i as f64 * size)
+                    .take_while(|t| *t <= max)
+                    .take(MAX_BOUNDARIES)
+                    .collect()
+            }
+        }
+    }
+
+    /// Get a short label for a tick at a time. Only the parts of the time that change between
+    /// ticks are shown, except at the start of a day or year, where the larger unit is shown
+    /// instead.
+    pub fn label(&self, time: f64) -> String {
+        let at: DateTime<Utc> = datetime(time);
+        let midnight: bool = at.num_seconds_from_midnight() == 0;
+        let pattern: &str = match self.unit {
+            TimeUnit::Second => "%H:%M:%S",
+            TimeUnit::Minute | TimeUnit::Hour if midnight => "%b %d",
+            TimeUnit::Minute | TimeUnit::Hour => "%H:%M",
+            TimeUnit::Day | TimeUnit::Week if at.ordinal0() == 0 => "%Y",
+            TimeUnit::Day | TimeUnit::Week => "%b %d",
+            TimeUnit::Month if at.month0() == 0 => "%Y",
+            TimeUnit::Month => "%b",
+            TimeUnit::Year => "%Y",
+        };
+        at.format(pattern).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-03-05 21:10:00 UTC, a Tuesday.
+    const TUESDAY_EVENING: f64 = 1709673000.0;
+
+    fn labels(step: TimeStep, min: f64, max: f64) -> Vec<String> {
+        step.boundaries(min, max).into_iter().map(|t| step.label(t)).collect()
+    }
+
+    #[test]
+    fn steps_grow() {
+        assert!((1..40).all(|i| TimeStep::nth(i).seconds() > TimeStep::nth(i - 1).seconds()));
+        assert_eq!(TimeStep::nth(TimeStep::index_at_least(50.0)), TimeStep { unit: TimeUnit::Minute, count: 1 });
+        assert_eq!(TimeStep::nth(TimeStep::index_at_least(4e9)), TimeStep { unit: TimeUnit::Year, count: 200 });
+    }
+
+    #[test]
+    fn hours_show_the_date_at_midnight() {
+        let step: TimeStep = TimeStep { unit: TimeUnit::Hour, count: 1 };
+        assert_eq!(labels(step, TUESDAY_EVENING, TUESDAY_EVENING + 4.0 * HOUR), vec!["22:00", "23:00", "Mar 06", "01:00"]);
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        let step: TimeStep = TimeStep { unit: TimeUnit::Week, count: 1 };
+        assert_eq!(labels(step, TUESDAY_EVENING, TUESDAY_EVENING + 14.0 * DAY), vec!["Mar 11", "Mar 18"]);
+    }
+
+    #[test]
+    fn months_show_the_year_in_january() {
+        let step: TimeStep = TimeStep { unit: TimeUnit::Month, count: 3 };
+        assert_eq!(labels(step, TUESDAY_EVENING, TUESDAY_EVENING + 365.0 * DAY), vec!["Apr", "Jul", "Oct", "2025"]);
+    }
+
+    #[test]
+    fn years_land_on_round_years() {
+        let step: TimeStep = TimeStep { unit: TimeUnit::Year, count: 5 };
+        assert_eq!(labels(step, 0.0, 20.0 * 365.25 * DAY), vec!["1970", "1975", "1980", "1985", "1990"]);
+    }
+}
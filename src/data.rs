@@ -1,8 +1,12 @@
+use std::fmt::{self, Display, Formatter};
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Result};
 use std::ops::{Add, Sub};
+use std::str::FromStr;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
 
 /// Basic structure for representing a 2D position on a plot. Since plots use only unsigned integer
 /// values, this struct only supports unsigned integers.
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Debug)]
 pub struct PVec2 {
     pub x: u16,
     pub y: u16,
@@ -10,25 +14,6 @@ pub struct PVec2 {
 
 impl PVec2 {
     pub fn new(x: u16, y: u16) -> PVec2 { PVec2 { x, y } }
-
-    /// Determine what values to add to reach another point.
-    ///
-    /// ## Example
-    /// ```rs
-    /// let a: Point = Point::new(1, 3)
-    /// let b: Point = Point::new(2, 4)
-    /// let c: Point = a.to(b) // returns Point(1, 1)
-    /// let d: bool = a + c == b // returns true
-    /// ```
-    pub fn to(&self, other: &PVec2) -> PVec2 { PVec2::new(other.x - self.x, other.y - self.y) }
-
-    fn distance(self: &PVec2, rhs: &PVec2) -> f32 {
-        let lx: f32 = self.x as f32;
-        let ly: f32 = self.y as f32;
-        let rx: f32 = rhs.x as f32;
-        let ry: f32 = rhs.y as f32;
-        ((lx - rx).powi(2) + (ly - ry).powi(2)).sqrt()
-    }
 }
 
 impl Add for PVec2 {
@@ -48,7 +33,7 @@ impl Sub for PVec2 {
 
 /// Basic structure for representing 2D points on any arbitrary coordinate plane. Uses floats to
 /// allow for decimal values, and can be overlaid onto a ScaledViewBox to get proper coordinates.
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Debug)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
@@ -67,12 +52,6 @@ impl Vec2 {
     /// let d: bool = a + c == b // returns true
     /// ```
     pub fn to(&self, other: &Vec2) -> Vec2 { Vec2::new(other.x - self.x, other.y - self.y) }
-
-    /// Get the distance between two points.
-    pub fn distance(&self, other: &Vec2) -> f32 {
-        let dist = self.to(other);
-        (dist.x * dist.x) + (dist.y * dist.y).sqrt()
-    }
 }
 
 impl Add for Vec2 {
@@ -89,4 +68,474 @@ impl Sub for Vec2 {
     fn sub(self, rhs: Self) -> Self::Output {
         Vec2::new(self.x - rhs.x, self.y - rhs.y)
     }
-}
\ No newline at end of file
+}
+
+/// A named list of points, to be plotted together on a chart.
+pub struct Series {
+    pub name: String,
+    pub points: Vec<Vec2>,
+}
+
+impl Series {
+    pub fn new(name: &str, points: Vec<Vec2>) -> Series { Series { name: name.to_string(), points } }
+}
+
+/// Reference to a column of a table, either by its position or by its header name. When parsed
+/// from a string, positions start at 1.
+#[derive(PartialEq, Clone, Debug)]
+pub enum ColumnRef {
+    Index(usize),
+    Name(String),
+}
+
+impl FromStr for ColumnRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.parse::<usize>() {
+            Ok(0) => Err("column numbers start at 1".to_string()),
+            Ok(n) => Ok(ColumnRef::Index(n - 1)),
+            Err(_) => Ok(ColumnRef::Name(s.to_string())),
+        }
+    }
+}
+
+impl Display for ColumnRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnRef::Index(i) => write!(f, "{}", i + 1),
+            ColumnRef::Name(name) => write!(f, "\"{}\"", name),
+        }
+    }
+}
+
+/// How the timestamps in a column are written.
+#[derive(PartialEq, Clone, Debug)]
+pub enum TimeFormat {
+    /// RFC 3339 timestamps, or Unix timestamps in seconds. Numbers too large to be seconds are
+    /// taken as milliseconds.
+    Auto,
+    /// RFC 3339 timestamps, such as "2024-03-05T14:30:00Z".
+    Rfc3339,
+    /// Unix timestamps in seconds.
+    UnixSeconds,
+    /// Unix timestamps in milliseconds.
+    UnixMillis,
+    /// A strftime-style pattern, such as "%Y-%m-%d %H:%M". Times without a time zone are taken
+    /// as UTC.
+    Pattern(String),
+}
+
+/// Smallest number that `TimeFormat::Auto` takes as milliseconds rather than seconds. As seconds,
+/// this would be in the year 5138.
+const AUTO_MILLIS: f64 = 1e11;
+
+impl TimeFormat {
+    /// Parse a timestamp into seconds since the Unix epoch.
+    pub fn parse(&self, field: &str) -> Option<f64> {
+        let field: &str = field.trim();
+        match self {
+            TimeFormat::Auto => Self::parse_rfc3339(field).or_else(|| {
+                let n: f64 = field.parse().ok()?;
+                Some(if n.abs() >= AUTO_MILLIS { n / 1000.0 } else { n })
+            }),
+            TimeFormat::Rfc3339 => Self::parse_rfc3339(field),
+            TimeFormat::UnixSeconds => field.parse().ok(),
+            TimeFormat::UnixMillis => field.parse::<f64>().ok().map(|n| n / 1000.0),
+            TimeFormat::Pattern(pattern) => DateTime::parse_from_str(field, pattern).map(|t| t.to_utc())
+                .or_else(|_| NaiveDateTime::parse_from_str(field, pattern).map(|t| t.and_utc()))
+                .or_else(|_| NaiveDate::parse_from_str(field, pattern).map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()))
+                .ok()
+                .map(|t| t.timestamp() as f64 + t.timestamp_subsec_nanos() as f64 / 1e9),
+        }
+    }
+
+    fn parse_rfc3339(field: &str) -> Option<f64> {
+        let time: DateTime<FixedOffset> = DateTime::parse_from_rfc3339(field).ok()?;
+        Some(time.timestamp() as f64 + time.timestamp_subsec_nanos() as f64 / 1e9)
+    }
+}
+
+impl FromStr for TimeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(TimeFormat::Auto),
+            "rfc3339" => Ok(TimeFormat::Rfc3339),
+            "unix" => Ok(TimeFormat::UnixSeconds),
+            "unix-ms" => Ok(TimeFormat::UnixMillis),
+            _ if s.contains('%') => Ok(TimeFormat::Pattern(s.to_string())),
+            _ => Err(format!("unknown time format \"{}\" (expected auto, rfc3339, unix, unix-ms or a strftime pattern)", s)),
+        }
+    }
+}
+
+/// A single record of input, as returned by a format's parser.
+pub enum Record {
+    /// Fields in column order.
+    Fields(Vec<String>),
+    /// Fields along with the name of the column they belong in.
+    Named(Vec<(String, String)>),
+}
+
+/// An input format. Each format parses one line of input at a time into a record; Blank lines
+/// are always skipped.
+pub struct Format {
+    pub name: &'static str,
+    /// Short description, listed when an unknown format is asked for.
+    pub description: &'static str,
+    /// Parse a line of input. Returns `None` if the line should be skipped.
+    parse: fn(&str) -> std::result::Result<Option<Record>, String>,
+    /// Whether the first record may be a header row.
+    headers: bool,
+}
+
+/// Registry of all supported input formats.
+pub const FORMATS: &[Format] = &[
+    Format { name: "float", description: "one number per line", parse: parse_float, headers: false },
+    Format { name: "csv", description: "comma-separated values", parse: parse_csv, headers: true },
+    Format { name: "tsv", description: "tab-separated values", parse: parse_tsv, headers: true },
+    Format { name: "whitespace", description: "columns separated by spaces or tabs", parse: parse_whitespace, headers: true },
+    Format { name: "jsonl", description: "JSON Lines; one object or array per line", parse: parse_jsonl, headers: false },
+];
+
+/// Find an input format by name.
+pub fn find_format(name: &str) -> Result<&'static Format> {
+    FORMATS.iter().find(|f| f.name == name).ok_or_else(|| Error::new(
+        ErrorKind::InvalidInput,
+        format!(
+            "unknown format \"{}\" (expected one of: {})",
+            name,
+            FORMATS.iter().map(|f| format!("{} ({})", f.name, f.description)).collect::<Vec<String>>().join(", "),
+        ),
+    ))
+}
+
+fn parse_float(line: &str) -> std::result::Result<Option<Record>, String> {
+    Ok(Some(Record::Fields(vec![line.trim().to_string()])))
+}
+
+/// Split a line on a delimiter. Fields may be wrapped in double quotes, in which case they can
+/// contain the delimiter, and `""` stands for a literal quote. Quoted fields can't span lines.
+fn split_quoted(line: &str, delimiter: char) -> std::result::Result<Vec<String>, String> {
+    let mut fields: Vec<String> = Vec::new();
+    let mut field: String = String::new();
+    let mut quoted: bool = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => { field.push('"'); chars.next(); }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => { field.clear(); quoted = true; }
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    if quoted { return Err("unterminated quoted field".to_string()) }
+    fields.push(field.trim().to_string());
+    Ok(fields)
+}
+
+fn parse_csv(line: &str) -> std::result::Result<Option<Record>, String> {
+    split_quoted(line, ',').map(|f| Some(Record::Fields(f)))
+}
+
+fn parse_tsv(line: &str) -> std::result::Result<Option<Record>, String> {
+    Ok(Some(Record::Fields(line.split('\t').map(|f| f.trim().to_string()).collect())))
+}
+
+fn parse_whitespace(line: &str) -> std::result::Result<Option<Record>, String> {
+    // allow gnuplot-style comments
+    if line.trim_start().starts_with('#') { return Ok(None) }
+    Ok(Some(Record::Fields(line.split_whitespace().map(|f| f.to_string()).collect())))
+}
+
+/// Convert a JSON value to a field. Strings are used as-is, and anything else is written as JSON.
+fn json_field(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn parse_jsonl(line: &str) -> std::result::Result<Option<Record>, String> {
+    match serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e))? {
+        serde_json::Value::Object(map) => Ok(Some(Record::Named(
+            map.iter().map(|(k, v)| (k.clone(), json_field(v))).collect()
+        ))),
+        serde_json::Value::Array(values) => Ok(Some(Record::Fields(values.iter().map(json_field).collect()))),
+        other => Ok(Some(Record::Fields(vec![json_field(&other)]))),
+    }
+}
+
+/// Parsed input data. Values are kept as text until they are selected, so that columns can hold
+/// labels as well as numbers.
+#[derive(Default)]
+pub struct Table {
+    /// Column names. Empty if the input had no header row.
+    pub headers: Vec<String>,
+    /// Rows of fields, along with the line of input that they came from.
+    rows: Vec<(usize, Vec<String>)>,
+    /// Number of rows dropped from the start of the table.
+    dropped: usize,
+}
+
+impl Table {
+    /// Read and parse an entire input.
+    pub fn read(input: impl Read, format: &Format) -> Result<Table> {
+        let mut table: Table = Table::default();
+        for (n, line) in BufReader::new(input).lines().enumerate() {
+            table.push_line(format, n + 1, line?.as_str())?;
+        }
+        Ok(table)
+    }
+
+    /// Parse a line of input and add it to the table.
+    pub fn push_line(&mut self, format: &Format, line_number: usize, line: &str) -> Result<()> {
+        if line.trim().is_empty() { return Ok(()) }
+        let record: Option<Record> = (format.parse)(line).map_err(|e| Error::new(
+            ErrorKind::InvalidData,
+            format!("line {}: {}", line_number, e),
+        ))?;
+        match record {
+            Some(Record::Fields(fields)) => {
+                // treat the first record as a header row if nothing in it is a number
+                if format.headers && self.rows.is_empty() && self.headers.is_empty()
+                    && fields.iter().all(|f| f.parse::<f32>().is_err()) {
+                    self.headers = fields;
+                } else {
+                    self.rows.push((line_number, fields));
+                }
+            }
+            Some(Record::Named(fields)) => {
+                let mut row: Vec<String> = vec![String::new(); self.headers.len()];
+                for (name, value) in fields {
+                    match self.headers.iter().position(|h| *h == name) {
+                        Some(i) => row[i] = value,
+                        None => {
+                            self.headers.push(name);
+                            row.push(value);
+                        }
+                    }
+                }
+                self.rows.push((line_number, row));
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Remove the first `count` rows from the table. Row numbers, used as X values when there is
+    /// no X column, keep counting from the first row that was ever added.
+    pub fn drop_rows(&mut self, count: usize) {
+        let count: usize = count.min(self.rows.len());
+        self.rows.drain(..count);
+        self.dropped += count;
+    }
+
+    /// Remove the last row of the table, if it has any.
+    pub fn pop_row(&mut self) {
+        self.rows.pop();
+    }
+
+    /// Get a copy of the table with only its last row, so that it can be checked on its own. The
+    /// row is padded with empty fields to at least `columns` columns, and row numbers stay the
+    /// same as in the whole table.
+    pub fn last_row(&self, columns: usize) -> Table {
+        let start: usize = self.rows.len().saturating_sub(1);
+        let mut rows: Vec<(usize, Vec<String>)> = self.rows[start..].to_vec();
+        for (_, row) in rows.iter_mut() {
+            if row.len() < columns { row.resize(columns, String::new()); }
+        }
+        Table { headers: self.headers.clone(), rows, dropped: self.dropped + start }
+    }
+
+    /// Get the number of columns in the table.
+    pub fn columns(&self) -> usize {
+        self.rows.iter().map(|r| r.1.len()).max().unwrap_or(0).max(self.headers.len())
+    }
+
+    /// Get the number of rows in the table.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Check if the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Get the name of a column. Columns without a header are named by their position.
+    pub fn column_name(&self, index: usize) -> String {
+        self.headers.get(index).cloned().unwrap_or_else(|| format!("column {}", index + 1))
+    }
+
+    /// Get the header of a column, or an empty string if it has none.
+    pub fn header(&self, column: &ColumnRef) -> String {
+        self.column_index(column).ok().and_then(|i| self.headers.get(i).cloned()).unwrap_or_default()
+    }
+
+    /// Describe a column for error messages.
+    fn describe_column(&self, index: usize) -> String {
+        match self.headers.get(index) {
+            Some(name) => format!("column \"{}\"", name),
+            None => format!("column {}", index + 1),
+        }
+    }
+
+    /// Find the position of a column.
+    pub fn column_index(&self, column: &ColumnRef) -> Result<usize> {
+        match column {
+            ColumnRef::Index(i) if *i < self.columns() => Ok(*i),
+            ColumnRef::Index(i) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("column {} is out of range (input has {} columns)", i + 1, self.columns()),
+            )),
+            ColumnRef::Name(name) => self.headers.iter().position(|h| h == name).ok_or_else(|| Error::new(
+                ErrorKind::InvalidInput,
+                if self.headers.is_empty() {
+                    format!("no column named \"{}\" (input has no header row)", name)
+                } else {
+                    format!("no column named \"{}\" (available columns: {})", name, self.headers.join(", "))
+                },
+            )),
+        }
+    }
+
+    /// Get the text of every value in a column. Missing values are empty.
+    pub fn labels(&self, column: &ColumnRef) -> Result<Vec<String>> {
+        let index: usize = self.column_index(column)?;
+        Ok(self.rows.iter().map(|(_, row)| row.get(index).cloned().unwrap_or_default()).collect())
+    }
+
+    /// Parse every value in a column. `kind` describes what the values should be, for error
+    /// messages.
+    fn parse_column<T>(&self, column: &ColumnRef, kind: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>> {
+        let index: usize = self.column_index(column)?;
+        self.rows.iter().map(|(n, row)| {
+            let field: &str = row.get(index).map(|f| f.as_str()).unwrap_or("");
+            parse(field).ok_or_else(|| Error::new(
+                ErrorKind::InvalidData,
+                if field.is_empty() {
+                    format!("line {}: missing value in {}", n, self.describe_column(index))
+                } else {
+                    format!("line {}: could not parse \"{}\" in {} as {}", n, field, self.describe_column(index), kind)
+                },
+            ))
+        }).collect()
+    }
+
+    /// Get every value in a column as a number. Infinite values and NaN (or numbers too big to
+    /// store) are treated as text that isn't a number, since they can't be plotted.
+    pub fn numbers(&self, column: &ColumnRef) -> Result<Vec<f32>> {
+        self.parse_column(column, "a number", |field| field.parse::<f32>().ok().filter(|n| n.is_finite()))
+    }
+
+    /// Get every value in a column as a time, in seconds since the Unix epoch.
+    pub fn times(&self, column: &ColumnRef, format: &TimeFormat) -> Result<Vec<f64>> {
+        self.parse_column(column, "a time", |field| format.parse(field).filter(|t| t.is_finite()))
+    }
+
+    /// Get a series for each Y column, using another column for the X values. If no X column is
+    /// given, the row number is used instead.
+    pub fn series(&self, x: Option<&ColumnRef>, y: &[ColumnRef]) -> Result<Vec<Series>> {
+        let xs: Vec<f32> = match x {
+            Some(column) => self.numbers(column)?,
+            None => (self.dropped..self.dropped + self.len()).map(|i| i as f32).collect(),
+        };
+        self.series_with_x(&xs, y)
+    }
+
+    /// Get a series for each Y column, using a column of times for the X values. Times are given
+    /// in seconds since the earliest one (rounded down to a whole second), which is returned as a
+    /// Unix timestamp along with the series.
+    pub fn time_series(&self, x: &ColumnRef, format: &TimeFormat, y: &[ColumnRef]) -> Result<(i64, Vec<Series>)> {
+        let times: Vec<f64> = self.times(x, format)?;
+        let origin: f64 = times.iter().copied().fold(f64::INFINITY, f64::min);
+        let origin: i64 = if origin.is_finite() { origin.floor() as i64 } else { 0 };
+        let xs: Vec<f32> = times.iter().map(|t| (t - origin as f64) as f32).collect();
+        Ok((origin, self.series_with_x(&xs, y)?))
+    }
+
+    /// Get a series for each Y column, with the given X values.
+    fn series_with_x(&self, xs: &[f32], y: &[ColumnRef]) -> Result<Vec<Series>> {
+        y.iter().map(|column| {
+            let ys: Vec<f32> = self.numbers(column)?;
+            let name: String = self.column_name(self.column_index(column)?);
+            Ok(Series::new(name.as_str(), xs.iter().zip(ys).map(|(x, y)| Vec2::new(*x, y)).collect()))
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(format: &str, input: &str) -> Table {
+        Table::read(input.as_bytes(), find_format(format).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn csv_quoted_fields() {
+        let table: Table = table("csv", "name,value\n\"a, b\",1\n\"say \"\"hi\"\"\",2\n");
+        assert_eq!(table.headers, vec!["name", "value"]);
+        assert_eq!(table.labels(&ColumnRef::Index(0)).unwrap(), vec!["a, b", "say \"hi\""]);
+    }
+
+    #[test]
+    fn header_only_when_nothing_is_a_number() {
+        assert!(table("whitespace", "a 5\nb 6\n").headers.is_empty());
+        assert_eq!(table("whitespace", "# comment\nx y\n1 2\n").headers, vec!["x", "y"]);
+    }
+
+    #[test]
+    fn jsonl_named_columns() {
+        let table: Table = table("jsonl", "{\"x\": 1, \"y\": 2}\n{\"y\": 4, \"z\": \"q\"}\n");
+        assert_eq!(table.headers, vec!["x", "y", "z"]);
+        assert_eq!(table.numbers(&ColumnRef::Name("y".to_string())).unwrap(), vec![2.0, 4.0]);
+        assert_eq!(table.labels(&ColumnRef::Name("z".to_string())).unwrap(), vec!["", "q"]);
+    }
+
+    #[test]
+    fn dropped_rows_keep_their_row_numbers() {
+        let mut table: Table = table("float", "5\n6\n7\n");
+        table.drop_rows(2);
+        let series: Vec<Series> = table.series(None, &[ColumnRef::Index(0)]).unwrap();
+        assert_eq!(series[0].points, vec![Vec2::new(2.0, 7.0)]);
+    }
+
+    #[test]
+    fn column_errors() {
+        let table: Table = table("csv", "a,b\n1,x\n");
+        let error: Error = table.numbers(&ColumnRef::Name("b".to_string())).unwrap_err();
+        assert_eq!(error.to_string(), "line 2: could not parse \"x\" in column \"b\" as a number");
+        assert!(table.column_index(&ColumnRef::Index(2)).is_err());
+        for bad in ["inf", "-inf", "NaN", "1e39"] {
+            let error: Error = self::table("float", &format!("1\n{}\n", bad)).numbers(&ColumnRef::Index(0)).unwrap_err();
+            assert_eq!(error.to_string(), format!("line 2: could not parse \"{}\" in column 1 as a number", bad));
+        }
+        assert_eq!("0".parse::<ColumnRef>(), Err("column numbers start at 1".to_string()));
+    }
+
+    #[test]
+    fn time_formats() {
+        assert_eq!(TimeFormat::Auto.parse("2024-03-05T21:10:00Z"), Some(1709673000.0));
+        assert_eq!(TimeFormat::Auto.parse("2024-03-05T22:10:00+01:00"), Some(1709673000.0));
+        assert_eq!(TimeFormat::Auto.parse("1709673000"), Some(1709673000.0));
+        assert_eq!(TimeFormat::Auto.parse("1709673000500"), Some(1709673000.5));
+        assert_eq!(TimeFormat::UnixMillis.parse("1500"), Some(1.5));
+        assert_eq!("%d/%m/%Y %H:%M".parse::<TimeFormat>().unwrap().parse("05/03/2024 21:10"), Some(1709673000.0));
+        assert_eq!("%Y-%m-%d".parse::<TimeFormat>().unwrap().parse("2024-03-05"), Some(1709596800.0));
+        assert_eq!(TimeFormat::Rfc3339.parse("yesterday"), None);
+    }
+
+    #[test]
+    fn time_series_are_relative_to_the_first_time() {
+        let table: Table = table("csv", "t,v\n2024-03-05T21:10:30Z,1\n2024-03-05T21:10:00.5Z,2\n");
+        let (origin, series) = table.time_series(&ColumnRef::Index(0), &TimeFormat::Auto, &[ColumnRef::Index(1)]).unwrap();
+        assert_eq!(origin, 1709673000);
+        assert_eq!(series[0].points, vec![Vec2::new(30.0, 1.0), Vec2::new(0.5, 2.0)]);
+    }
+}
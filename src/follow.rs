@@ -0,0 +1,188 @@
+/// Live input. Lines are read on their own thread as they arrive, and added to a table that only
+/// keeps a sliding window of the most recent rows.
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read};
+use std::str::FromStr;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::data::{Format, Table};
+
+/// How much of the input to keep.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Window {
+    /// The last N rows.
+    Points(usize),
+    /// Rows that arrived in the last N seconds.
+    Seconds(Duration),
+}
+
+impl FromStr for Window {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("invalid window \"{}\" (expected a number of points, such as 500, or seconds, such as 30s)", s);
+        match s.strip_suffix('s') {
+            Some(seconds) => match seconds.parse::<f32>().ok().and_then(|s| Duration::try_from_secs_f32(s).ok()) {
+                Some(duration) if !duration.is_zero() => Ok(Window::Seconds(duration)),
+                _ => Err(error()),
+            },
+            None => match s.parse::<usize>() {
+                Ok(points) if points > 0 => Ok(Window::Points(points)),
+                _ => Err(error()),
+            },
+        }
+    }
+}
+
+/// Input that is still being read. Rows are added to the table as their lines arrive.
+pub struct Stream {
+    pub table: Table,
+    format: &'static Format,
+    window: Option<Window>,
+    lines: Receiver<io::Result<String>>,
+    /// Number of lines received so far.
+    line_number: usize,
+    /// Number of columns in the widest row added so far.
+    columns: usize,
+    /// When each row of the table arrived, in order.
+    arrivals: VecDeque<Instant>,
+    /// Set once the input has ended.
+    pub ended: bool,
+}
+
+impl Stream {
+    /// Start reading an input in the background.
+    pub fn new(input: impl Read + Send + 'static, format: &'static Format, window: Option<Window>) -> Stream {
+        let (sender, lines) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(input).lines() {
+                // stop if nobody is listening any more
+                if sender.send(line).is_err() { break }
+            }
+        });
+        Stream { table: Table::default(), format, window, lines, line_number: 0, columns: 0, arrivals: VecDeque::new(), ended: false }
+    }
+
+    /// Add a line of input to the table, if `check` accepts the row it adds. Lines that can't be
+    /// parsed, or that `check` fails on with `ErrorKind::InvalidData`, are skipped with a warning,
+    /// so that one bad line doesn't stop the input from being followed. Other errors are returned.
+    fn push(&mut self, line: &str, now: Instant, check: &impl Fn(&Table) -> io::Result<()>) -> io::Result<()> {
+        self.line_number += 1;
+        let rows: usize = self.table.len();
+        let result: io::Result<()> = self.table.push_line(self.format, self.line_number, line).and_then(|_| {
+            // not every line adds a row (blank lines, comments and headers don't)
+            if self.table.len() == rows { return Ok(()) }
+            // rows shorter than the ones before are missing values, rather than missing columns
+            let row: Table = self.table.last_row(self.columns);
+            check(&row)?;
+            self.columns = row.columns();
+            self.arrivals.push_back(now);
+            Ok(())
+        });
+        match result {
+            Err(e) if e.kind() == ErrorKind::InvalidData => {
+                if self.table.len() > rows { self.table.pop_row(); }
+                eprintln!("warning: {} (line skipped)", e);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Drop rows that are outside the window. Returns whether any were dropped.
+    fn trim(&mut self, now: Instant) -> bool {
+        let count: usize = match self.window {
+            None => 0,
+            Some(Window::Points(points)) => self.arrivals.len().saturating_sub(points),
+            Some(Window::Seconds(window)) => {
+                self.arrivals.iter().take_while(|arrival| now.duration_since(**arrival) > window).count()
+            }
+        };
+        self.table.drop_rows(count);
+        self.arrivals.drain(..count);
+        count > 0
+    }
+
+    /// Wait up to `timeout` for more input, then add every line that has arrived (checking each
+    /// new row with `check`, see `push()`), and drop rows that are outside the window. Returns
+    /// whether the table changed.
+    pub fn update(&mut self, timeout: Duration, check: impl Fn(&Table) -> io::Result<()>) -> io::Result<bool> {
+        if self.ended { return Ok(false) }
+        let mut changed: bool = false;
+        let mut next: Result<io::Result<String>, TryRecvError> = self.lines.recv_timeout(timeout).map_err(|e| match e {
+            RecvTimeoutError::Timeout => TryRecvError::Empty,
+            RecvTimeoutError::Disconnected => TryRecvError::Disconnected,
+        });
+        loop {
+            match next {
+                Ok(line) => {
+                    self.push(&line?, Instant::now(), &check)?;
+                    changed = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.ended = true;
+                    break
+                }
+            }
+            next = self.lines.try_recv();
+        }
+        Ok(self.trim(Instant::now()) || changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{find_format, ColumnRef, Series, Vec2};
+
+    #[test]
+    fn windows() {
+        assert_eq!("500".parse::<Window>(), Ok(Window::Points(500)));
+        assert_eq!("2.5s".parse::<Window>(), Ok(Window::Seconds(Duration::from_millis(2500))));
+        assert!("0".parse::<Window>().is_err());
+        assert!("fast".parse::<Window>().is_err());
+        for window in ["0s", "-1s", "infs", "nans", "1e30s"] {
+            assert!(window.parse::<Window>().is_err(), "{}", window);
+        }
+    }
+
+    #[test]
+    fn streams_keep_the_last_points() {
+        let input: &'static [u8] = b"1\n2\n\n3\n4\n";
+        let mut stream: Stream = Stream::new(input, find_format("float").unwrap(), Some(Window::Points(2)));
+        while !stream.ended {
+            stream.update(Duration::from_secs(1), |_| Ok(())).unwrap();
+        }
+        let series: Vec<Series> = stream.table.series(None, &[ColumnRef::Index(0)]).unwrap();
+        assert_eq!(series[0].points, vec![Vec2::new(2.0, 3.0), Vec2::new(3.0, 4.0)]);
+    }
+
+    #[test]
+    fn streams_skip_bad_lines() {
+        let input: &'static [u8] = b"1 5\n2 6\nfoo 7\n8\n3 9\n";
+        let mut stream: Stream = Stream::new(input, find_format("whitespace").unwrap(), None);
+        let check = |rows: &Table| rows.series(None, &[ColumnRef::Index(0), ColumnRef::Index(1)]).map(|_| ());
+        while !stream.ended {
+            stream.update(Duration::from_secs(1), check).unwrap();
+        }
+        let series: Vec<Series> = stream.table.series(None, &[ColumnRef::Index(0)]).unwrap();
+        assert_eq!(series[0].points, vec![Vec2::new(0.0, 1.0), Vec2::new(1.0, 2.0), Vec2::new(2.0, 3.0)]);
+        // a column that doesn't exist isn't a problem with the line, so it isn't skipped
+        let mut stream: Stream = Stream::new(&b"1\n"[..], find_format("float").unwrap(), None);
+        let check = |rows: &Table| rows.series(None, &[ColumnRef::Index(3)]).map(|_| ());
+        assert!(stream.update(Duration::from_secs(1), check).is_err());
+    }
+
+    #[test]
+    fn streams_drop_old_rows() {
+        let mut stream: Stream = Stream::new(&b""[..], find_format("float").unwrap(), Some(Window::Seconds(Duration::from_secs(10))));
+        let start: Instant = Instant::now();
+        stream.push("1", start, &|_| Ok(())).unwrap();
+        stream.push("2", start + Duration::from_secs(5), &|_| Ok(())).unwrap();
+        assert!(!stream.trim(start + Duration::from_secs(10)));
+        assert!(stream.trim(start + Duration::from_secs(12)));
+        assert_eq!(stream.table.len(), 1);
+    }
+}
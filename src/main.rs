@@ -16,12 +16,33 @@
 mod renderer;
 mod chart;
 mod data;
+mod follow;
+mod viewer;
+#[cfg(test)]
+mod snapshot;
 
-use std::io::Result;
+use std::fs::File;
+use std::io::{stdout, Error, ErrorKind, Result};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use crate::chart::bar::{BarChart, Orientation};
+use crate::chart::hist::{Binning, HistChart};
+use crate::chart::legend::LegendPosition;
+use crate::chart::line::LineChart;
+use crate::chart::base::{BaseChart, Chart, DEFAULT_MARKERS};
+use crate::chart::scatter::ScatterChart;
+use crate::renderer::canvas::{Canvas, TextCanvas, WriteCanvas};
+use crate::renderer::color::{ColorChoice, ColorDepth};
 use crate::renderer::plot::Plot;
-use crate::data::PVec2;
-use crate::renderer::shapes::Line;
-use crate::renderer::terminal::get_size;
+use crate::data::{find_format, ColumnRef, Format, PVec2, Series, Table, TimeFormat};
+use crate::follow::{Stream, Window};
+use crate::renderer::shapes::{Line, Scale};
+use crate::renderer::raster::{ImageSize, PngCanvas};
+use crate::renderer::scene::{CELL_HEIGHT, CELL_WIDTH};
+use crate::renderer::svg::SvgCanvas;
+use crate::renderer::dots::DotMode;
+use crate::renderer::terminal::{get_size, poll_resize, Capabilities};
 use clap::{Parser, Subcommand};
 use clio::Input;
 
@@ -36,9 +57,94 @@ struct Args {
     #[arg(short, long, value_parser, default_value="-")]
     input_file: Input,
 
-    /// Format of data input.
-    #[arg(short, long, default_value="float")]
-    format: String,
+    /// Format of data input: float, csv, tsv, whitespace or jsonl. Defaults to "float" for line
+    /// charts and histograms, and "whitespace" for scatter and bar charts.
+    #[arg(short, long)]
+    format: Option<String>,
+
+    /// Column to use for X values, by number (starting at 1) or header name. For line charts,
+    /// defaults to the row number; for scatter charts, to the first column. For bar charts, this
+    /// is the label column, and for histograms, the column of values.
+    #[arg(short, long)]
+    x: Option<ColumnRef>,
+
+    /// Columns to use for Y values, separated by commas. Each column is drawn as its own series.
+    /// For line charts, defaults to the first column; for scatter charts, to every column other
+    /// than X. For bar charts, this is the value column.
+    #[arg(short, long, value_delimiter = ',')]
+    y: Vec<ColumnRef>,
+
+    /// Write the chart to a file instead of drawing it on the terminal: as an SVG image if the
+    /// file name ends in ".svg", a PNG image if it ends in ".png", or else as plain text.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Size of PNG images, in pixels, as WIDTHxHEIGHT. Unless --width and --height are given, the
+    /// chart gets one cell for every 12x24 pixels.
+    #[arg(long, default_value = "960x640")]
+    image_size: ImageSize,
+
+    /// Print the chart as ordinary lines of plain text, without color or cursor movement, even
+    /// with --color always. Charts are also printed as lines of text when stdout isn't a terminal,
+    /// but then --color always still adds color.
+    #[arg(long)]
+    plain: bool,
+
+    /// Show the chart on the whole terminal, and explore it with the keyboard: arrow keys pan, the
+    /// plus and minus keys zoom, r resets the view and q quits. Only line and scatter charts can
+    /// be shown this way.
+    #[arg(long, conflicts_with_all = ["output", "plain"])]
+    interactive: bool,
+
+    /// Title of the chart.
+    #[arg(short, long)]
+    title: Option<String>,
+
+    /// Name of the X axis. Defaults to the header of the X column, if it has one.
+    #[arg(long)]
+    x_label: Option<String>,
+
+    /// Name of the Y axis. Defaults to the header of the Y column, if there is only one.
+    #[arg(long)]
+    y_label: Option<String>,
+
+    /// Treat the X column as timestamps, for line and scatter charts: "auto" (RFC 3339 or Unix
+    /// seconds/milliseconds), "rfc3339", "unix", "unix-ms", or a strftime pattern such as
+    /// "%Y-%m-%d %H:%M:%S". Ticks are placed on calendar boundaries, in UTC.
+    #[arg(long)]
+    time: Option<TimeFormat>,
+
+    /// How lines, points and bars are drawn: "ascii" (one marker per cell), "braille" (2x4 dots
+    /// per cell), "half-block" (top and bottom half of each cell) or "quadrant" (2x2 blocks per
+    /// cell). Defaults to braille for line and scatter charts and half-block for bar charts and
+    /// histograms, on terminals that support Unicode, and ascii otherwise.
+    #[arg(long)]
+    marker: Option<DotMode>,
+
+    /// When to draw in color: never, auto or always. With auto, color is used when drawing on a
+    /// terminal that supports it, unless the NO_COLOR environment variable is set. Text files
+    /// written with --output, and charts printed with --plain, never have color.
+    #[arg(long, default_value = "auto")]
+    color: ColorChoice,
+
+    /// Scale of the X axis: linear, log10 (or log), log2 or ln. Only line and scatter charts
+    /// support logarithmic scales; values at or below zero are left out.
+    #[arg(long, default_value = "linear")]
+    x_scale: Scale,
+
+    /// Scale of the Y axis: linear, log10 (or log), log2 or ln.
+    #[arg(long, default_value = "linear")]
+    y_scale: Scale,
+
+    /// Where to put the legend: "auto" (in the emptiest corner of the chart, if there is more than
+    /// one series), "top", "right" or "none".
+    #[arg(long, default_value = "auto")]
+    legend: LegendPosition,
+
+    /// Adjusts the spacing of ticks on automatically scaled axes. Positive values give more
+    /// ticks, negative values give fewer.
+    #[arg(long, default_value_t = 0, allow_hyphen_values = true)]
+    tick_density: i8,
 
     /// Width of draw area. If undefined, uses full width of console.
     #[arg(short='W', long, default_value_t = 0)]
@@ -53,26 +159,234 @@ struct Args {
 enum Commands {
     /// Display test output
     Test {},
+    /// Draw a line chart from one or more columns of numbers.
+    Line {
+        /// Symbols to draw each series with, in order.
+        #[arg(short, long, default_value=DEFAULT_MARKERS)]
+        markers: String,
+
+        /// Keep reading the input, and redraw the chart in place as new lines arrive, until the
+        /// input ends.
+        #[arg(long)]
+        follow: bool,
+
+        /// With --follow, only show the last N points (such as "500"), or the points that arrived
+        /// in the last N seconds (such as "30s").
+        #[arg(long, requires = "follow")]
+        window: Option<Window>,
+    },
+    /// Draw a scatter chart from rows of numbers.
+    Scatter {
+        /// Marker characters for each series, in order.
+        #[arg(short, long, default_value=DEFAULT_MARKERS)]
+        markers: String,
+    },
+    /// Draw a bar chart from labeled values. Unless --x and --y are given, the first column is the
+    /// label, and the second is the value.
+    Bar {
+        /// Draw bars horizontally instead of vertically.
+        #[arg(long)]
+        horizontal: bool,
+    },
+    /// Draw a histogram from a list of numbers. Unless --x is given, uses the first column.
+    Hist {
+        /// Number of bins.
+        #[arg(short, long, conflicts_with_all = ["bin_width", "method"])]
+        bins: Option<usize>,
+
+        /// Width of each bin.
+        #[arg(short='w', long, conflicts_with = "method")]
+        bin_width: Option<f32>,
+
+        /// Method for choosing bins automatically: "sturges" or "fd" (Freedman–Diaconis).
+        #[arg(short, long, default_value="sturges")]
+        method: Binning,
+    },
+}
+
+/// Get the capabilities of wherever the chart is drawn: a plain text file, if `--output` was
+/// given, or else stdout. With `--plain`, stdout is treated like a pipe.
+fn capabilities(args: &Args) -> Capabilities {
+    match (&args.output, args.plain) {
+        (Some(_), _) => Capabilities::PLAIN,
+        (None, true) => Capabilities { tty: false, colors: ColorDepth::None, ..Capabilities::detect() },
+        (None, false) => Capabilities::detect(),
+    }
+}
+
+/// Get the colors to draw with, as chosen with `--color` for wherever the chart is drawn. Charts
+/// printed with `--plain` have none.
+fn colors(args: &Args) -> ColorDepth {
+    if args.plain && args.output.is_none() { return ColorDepth::None }
+    capabilities(args).choose_colors(args.color)
+}
+
+/// Check if the chart is written to a file with a certain extension.
+fn output_is(args: &Args, extension: &str) -> bool {
+    args.output.as_ref().and_then(|path| path.extension()).is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+/// Get the size of the chart, in cells: the size given with `--width`/`--height`, or else enough
+/// to fill the image for PNG output, or the terminal (leaving a line for the prompt).
+fn chart_size(args: &Args) -> (u16, u16) {
+    if args.width != 0 && args.height != 0 { return (args.width, args.height) }
+    let size: (u16, u16) = if output_is(args, "png") {
+        let cells = |pixels: u32, per_cell: u32| (pixels / per_cell).clamp(1, u16::MAX as u32) as u16;
+        (cells(args.image_size.width, CELL_WIDTH), cells(args.image_size.height, CELL_HEIGHT))
+    } else {
+        let (width, height) = get_size();
+        (width.max(1), height.saturating_sub(1).max(1))
+    };
+    (if args.width != 0 { args.width } else { size.0 }, if args.height != 0 { args.height } else { size.1 })
+}
+
+/// Create the canvas to draw a chart on: a file, if `--output` was given, plain lines of text on
+/// stdout, if it isn't a terminal (or `--plain` was given), or else the terminal. With
+/// `--interactive`, the chart is only laid out on the canvas, and the viewer draws it.
+fn open_canvas(args: &Args, width: u16, height: u16) -> Result<Box<dyn Canvas>> {
+    let capabilities: Capabilities = capabilities(args);
+    Ok(match &args.output {
+        None if args.interactive => Box::new(TextCanvas::new(width, height)),
+        Some(path) if output_is(args, "svg") => Box::new(SvgCanvas::new(File::create(path)?, width, height)),
+        Some(path) if output_is(args, "png") => Box::new(PngCanvas::new(File::create(path)?, width, height, args.image_size)),
+        Some(path) => Box::new(WriteCanvas::new(File::create(path)?, width, height)),
+        None if !capabilities.tty => Box::new(WriteCanvas::new(stdout(), width, height).colors(colors(args))),
+        None => Box::new(Plot::new(width, height).colors(colors(args))),
+    })
+}
+
+/// Get the marker mode to draw with: the one given with `--marker`, or else `best` if the output
+/// can show it. Unicode modes fall back to ASCII when drawing on a terminal that doesn't support
+/// Unicode.
+fn dot_mode(args: &Args, best: DotMode) -> DotMode {
+    let capabilities: Capabilities = capabilities(args);
+    match args.marker {
+        Some(mode) if mode.is_unicode() && args.output.is_none() && !capabilities.unicode => DotMode::Ascii,
+        Some(mode) => mode,
+        None => capabilities.dot_mode(best),
+    }
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+/// Read the input data as a table, using the selected format or a default one.
+fn read_table(input: &mut Input, format: &Option<String>, default: &str) -> Result<Table> {
+    Table::read(input, find_format(format.as_deref().unwrap_or(default))?)
+}
+
+/// Fail if a logarithmic scale or a time axis was requested for a chart that doesn't support one.
+fn require_linear(args: &Args, chart: &str) -> Result<()> {
+    if args.x_scale != Scale::Linear || args.y_scale != Scale::Linear {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{}s only support linear scales", chart)));
+    }
+    if args.time.is_some() {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{}s don't support time axes", chart)));
+    }
+    Ok(())
+}
+
+/// Get a series for each Y column. If `--time` was given, X values are times, and the Unix
+/// timestamp that they are relative to is returned too.
+fn read_series(table: &Table, args: &Args, x: Option<&ColumnRef>, y: &[ColumnRef]) -> Result<(Option<i64>, Vec<Series>)> {
+    let Some(format) = &args.time else { return Ok((None, table.series(x, y)?)) };
+    let Some(x) = x else {
+        return Err(Error::new(ErrorKind::InvalidInput, "--time needs an X column (--x)"));
+    };
+    if args.x_scale != Scale::Linear {
+        return Err(Error::new(ErrorKind::InvalidInput, "time axes can't use a logarithmic scale"));
+    }
+    let (origin, series) = table.time_series(x, format, y)?;
+    Ok((Some(origin), series))
+}
+
+/// How long to wait for more input before redrawing a chart with --follow, so that points
+/// outside a time window are dropped, and resizing the terminal is noticed, even when no input
+/// arrives.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Draw a line chart, and redraw it in place whenever more input arrives or the terminal is
+/// resized, until the input ends.
+fn follow_line(args: &mut Args, markers: &str, window: Option<Window>, width: u16, height: u16) -> Result<()> {
+    if args.output.is_some() {
+        return Err(Error::new(ErrorKind::InvalidInput, "--follow can't be used with --output"));
+    }
+    let format: &Format = find_format(args.format.as_deref().unwrap_or("float"))?;
+    let input: Input = std::mem::replace(&mut args.input_file, Input::std());
+    let mut stream: Stream = Stream::new(input, format, window);
+    let y: Vec<ColumnRef> = if args.y.is_empty() { vec![ColumnRef::Index(0)] } else { args.y.clone() };
+    let mut chart: LineChart = LineChart::new(open_canvas(args, width, height)?, Vec::new())
+        .markers(markers)
+        .dots(dot_mode(args, DotMode::Braille))
+        .scales(args.x_scale, args.y_scale);
+    let on_terminal: bool = capabilities(args).tty;
+    while !stream.ended {
+        let mut changed: bool = stream.update(FOLLOW_INTERVAL, |rows| read_series(rows, args, args.x.as_ref(), &y).map(|_| ()))?;
+        if on_terminal && poll_resize(Duration::ZERO)?.is_some() {
+            let (width, height) = chart_size(args);
+            chart.base_mut().set_size(width, height);
+            changed = true;
+        }
+        if !changed || stream.table.is_empty() { continue }
+        let (origin, series) = read_series(&stream.table, args, args.x.as_ref(), &y)?;
+        chart.base_mut().set_time_origin(origin);
+        chart.set_series(series);
+        let x_name: String = args.x.as_ref().map(|x| stream.table.header(x)).unwrap_or_default();
+        let y_name: String = if y.len() == 1 { stream.table.header(&y[0]) } else { String::new() };
+        configure_chart(chart.base_mut(), args, &x_name, &y_name);
+        chart.base_mut().canvas().clear();
+        chart.draw();
+    }
+    Ok(())
+}
+
+/// Draw a chart, or show it in the interactive viewer with `--interactive`.
+fn show(chart: &mut dyn Chart, args: &Args) -> Result<()> {
+    if !args.interactive {
+        chart.draw();
+        return Ok(());
+    }
+    let capabilities: Capabilities = capabilities(args);
+    if !capabilities.tty {
+        return Err(Error::new(ErrorKind::InvalidInput, "--interactive needs stdout to be a terminal"));
+    }
+    let (width, height) = get_size();
+    viewer::view(chart, width, height, colors(args))
+}
 
+/// Fail if `--interactive` was given for a chart that can't be shown in the viewer.
+fn require_static(args: &Args, chart: &str) -> Result<()> {
+    if args.interactive {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("{}s can't be shown with --interactive", chart)));
+    }
+    Ok(())
+}
+
+/// Set the title, axis names and tick spacing of a chart from the command line, or use default
+/// axis names.
+fn configure_chart(base: &mut BaseChart, args: &Args, x: &str, y: &str) {
+    base.set_title(args.title.as_deref().unwrap_or(""));
+    base.set_names(args.x_label.as_deref().unwrap_or(x), args.y_label.as_deref().unwrap_or(y));
+    base.set_marker_scale(args.tick_density);
+    base.set_legend(args.legend);
+    base.set_colors(&colors(args).palette());
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(mut args: Args) -> Result<()> {
     // set plot width and height
-    let size: (u16, u16) = get_size();
-    let width: u16;
-    if (args.width != 0) { width = args.width; }
-    else { width = size.0; }
-    let height: u16;
-    if (args.height != 0) { height = args.height; }
-    else { height = size.1 - 1; }
-
-    // create and clear the plot area
-    let plot: Plot = Plot::new(width, height);
-    plot.clear();
+    let (width, height) = chart_size(&args);
 
     match &args.command {
         Commands::Test{} => {
+            require_static(&args, "test output")?;
+            let plot: Plot = Plot::new(width, height);
             // print all our test characters
             plot.put('b', &PVec2::new(11, 5));
             plot.put('e', &PVec2::new(20, 12));
@@ -108,5 +422,101 @@ fn main() -> Result<()> {
             plot.finish();
             Ok(())
         }
+        Commands::Line{ markers, follow: true, window } => {
+            require_static(&args, "followed line chart")?;
+            let (markers, window) = (markers.clone(), *window);
+            follow_line(&mut args, &markers, window, width, height)
+        }
+        Commands::Line{ markers, .. } => {
+            let table: Table = read_table(&mut args.input_file, &args.format, "float")?;
+            let y: Vec<ColumnRef> = if args.y.is_empty() { vec![ColumnRef::Index(0)] } else { args.y.clone() };
+            let (origin, series) = read_series(&table, &args, args.x.as_ref(), &y)?;
+            let mut chart: LineChart = LineChart::new(open_canvas(&args, width, height)?, series)
+                .markers(markers)
+                .dots(dot_mode(&args, DotMode::Braille))
+                .scales(args.x_scale, args.y_scale);
+            if let Some(origin) = origin { chart = chart.time(origin); }
+            let x_name: String = args.x.as_ref().map(|x| table.header(x)).unwrap_or_default();
+            let y_name: String = if y.len() == 1 { table.header(&y[0]) } else { String::new() };
+            configure_chart(chart.base_mut(), &args, &x_name, &y_name);
+            show(&mut chart, &args)
+        }
+        Commands::Scatter{ markers } => {
+            let table: Table = read_table(&mut args.input_file, &args.format, "whitespace")?;
+            let x: ColumnRef = args.x.clone().unwrap_or(ColumnRef::Index(0));
+            let y: Vec<ColumnRef> = if args.y.is_empty() {
+                // use every column other than X
+                let x_index: usize = table.column_index(&x)?;
+                (0..table.columns()).filter(|i| *i != x_index).map(ColumnRef::Index).collect()
+            } else {
+                args.y.clone()
+            };
+            if y.is_empty() {
+                return Err(Error::new(ErrorKind::InvalidData, "expected at least two columns (x and y)"));
+            }
+            let (origin, series) = read_series(&table, &args, Some(&x), &y)?;
+            let mut chart: ScatterChart = ScatterChart::new(open_canvas(&args, width, height)?, series)
+                .markers(markers)
+                .dots(dot_mode(&args, DotMode::Braille))
+                .scales(args.x_scale, args.y_scale);
+            if let Some(origin) = origin { chart = chart.time(origin); }
+            let y_name: String = if y.len() == 1 { table.header(&y[0]) } else { String::new() };
+            configure_chart(chart.base_mut(), &args, &table.header(&x), &y_name);
+            show(&mut chart, &args)
+        }
+        Commands::Bar{ horizontal } => {
+            require_linear(&args, "bar chart")?;
+            require_static(&args, "bar chart")?;
+            let orientation: Orientation = if *horizontal { Orientation::Horizontal } else { Orientation::Vertical };
+            let table: Table = read_table(&mut args.input_file, &args.format, "whitespace")?;
+            if args.y.len() > 1 {
+                return Err(Error::new(ErrorKind::InvalidInput, "bar charts only support one Y column"));
+            }
+            // unless told otherwise, labels come from the first column and values from the first
+            // column after the labels; with only one column, bars are labeled by their position
+            let x: Option<ColumnRef> = args.x.clone().or((table.columns() > 1).then_some(ColumnRef::Index(0)));
+            let y: ColumnRef = match (args.y.first(), &x) {
+                (Some(y), _) => y.clone(),
+                (None, Some(x)) => ColumnRef::Index(if table.column_index(x)? == 0 { 1 } else { 0 }),
+                (None, None) => ColumnRef::Index(0),
+            };
+            let values: Vec<f32> = table.numbers(&y)?;
+            let labels: Vec<String> = match &x {
+                Some(x) => table.labels(x)?,
+                None => (1..=table.len()).map(|i| i.to_string()).collect(),
+            };
+            let mut chart: BarChart = BarChart::new(open_canvas(&args, width, height)?, labels.into_iter().zip(values).collect(), orientation)
+                .dots(dot_mode(&args, DotMode::HalfBlock));
+            let label_name: String = x.as_ref().map(|x| table.header(x)).unwrap_or_default();
+            let (x_name, y_name) = match orientation {
+                Orientation::Vertical => (label_name, table.header(&y)),
+                Orientation::Horizontal => (table.header(&y), label_name),
+            };
+            configure_chart(chart.base_mut(), &args, &x_name, &y_name);
+            chart.draw();
+            Ok(())
+        }
+        Commands::Hist{ bins, bin_width, method } => {
+            require_linear(&args, "histogram")?;
+            require_static(&args, "histogram")?;
+            let binning: Binning = match (bins, bin_width) {
+                (Some(0), _) => return Err(Error::new(ErrorKind::InvalidInput, "number of bins must be greater than zero")),
+                (Some(count), _) => Binning::Count(*count),
+                (_, Some(width)) if *width > 0.0 => Binning::Width(*width),
+                (_, Some(_)) => return Err(Error::new(ErrorKind::InvalidInput, "bin width must be greater than zero")),
+                _ => *method,
+            };
+            if !args.y.is_empty() {
+                return Err(Error::new(ErrorKind::InvalidInput, "histograms use --x to select the column of values"));
+            }
+            let table: Table = read_table(&mut args.input_file, &args.format, "float")?;
+            let x: &ColumnRef = args.x.as_ref().unwrap_or(&ColumnRef::Index(0));
+            let values: Vec<f32> = table.numbers(x)?;
+            let mut chart: HistChart = HistChart::new(open_canvas(&args, width, height)?, &values, binning)?
+                .dots(dot_mode(&args, DotMode::HalfBlock));
+            configure_chart(chart.base_mut(), &args, &table.header(x), "count");
+            chart.draw();
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,300 @@
+// clplot::renderer::canvas - render targets for shapes and charts
+//     Copyright (C) 2024  Dustin Thomas <stdio@cptlobster.dev>
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+/// Render targets. A canvas is a grid of character cells that shapes and charts can draw on;
+/// where the finished drawing ends up (the terminal, a string, a file...) depends on the canvas.
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::cmp::{max, min};
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use crate::data::PVec2;
+use crate::renderer::color::{ColorDepth, Style};
+use crate::renderer::scene::Scene;
+
+/// Something that can be drawn on. Points outside the canvas are moved to the nearest edge.
+///
+/// Canvases hold what is drawn on them in a text frame until they are finished. Every method
+/// except `frame()`, `frame_mut()` and `finish()` works on the frame by default, so a canvas only
+/// needs to override the ones that do something different.
+pub trait Canvas {
+    /// Get the frame that the canvas draws in.
+    fn frame(&self) -> &TextCanvas;
+
+    /// Get the frame that the canvas draws in, to change it.
+    fn frame_mut(&mut self) -> &mut TextCanvas;
+
+    /// Get the width of the canvas, in cells.
+    fn width(&self) -> u16 {
+        self.frame().width
+    }
+
+    /// Get the height of the canvas, in cells.
+    fn height(&self) -> u16 {
+        self.frame().height
+    }
+
+    /// Get the character at a location on the canvas. Locations outside the canvas are blank.
+    fn get(&self, point: &PVec2) -> char {
+        self.frame().get(point)
+    }
+
+    /// Place a character at a location on the canvas.
+    fn put(&self, character: char, point: &PVec2) {
+        self.frame().put(character, point);
+    }
+
+    /// Print a string on the canvas, one line per row. Whitespace will overwrite existing content.
+    fn put_str(&self, content: &str, start: &PVec2) {
+        self.frame().put_str(content, start);
+    }
+
+    /// Print a string on the canvas, one line per row. Whitespace will not overwrite existing
+    /// content.
+    fn put_str_transparent(&self, content: &str, start: &PVec2) {
+        self.frame().put_str_transparent(content, start);
+    }
+
+    /// Set the style that characters are drawn with from now on.
+    fn set_style(&self, style: Style) {
+        self.frame().set_style(style);
+    }
+
+    /// Get the style of the character at a location on the canvas.
+    fn style(&self, point: &PVec2) -> Style {
+        self.frame().style(point)
+    }
+
+    /// Draw a scene on the canvas. Character canvases draw it with characters, cell by cell.
+    fn draw_scene(&self, scene: &Scene) {
+        self.frame().draw_scene(scene);
+    }
+
+    /// Clear the canvas (fill the entire area with spaces).
+    fn clear(&self) {
+        self.frame().clear();
+    }
+
+    /// Change the size of the canvas, in cells. The canvas is cleared.
+    fn set_size(&mut self, width: u16, height: u16) {
+        self.frame_mut().set_size(width, height);
+    }
+
+    /// Get the contents of the canvas, as one string per row.
+    fn lines(&self) -> Vec<String> {
+        self.frame().lines()
+    }
+
+    /// Run this when you are done drawing, to send the drawing wherever it goes.
+    fn finish(&self);
+}
+
+/// Canvas that keeps its contents in memory, as plain text. Other canvases use this to hold their
+/// frame until it is output.
+pub struct TextCanvas {
+    pub width: u16,
+    pub height: u16,
+    /// Cells of the frame, row by row from the top.
+    cells: RefCell<Vec<char>>,
+    /// Style of each cell, in the same order as `cells`.
+    styles: RefCell<Vec<Style>>,
+    /// Style that new characters are drawn with.
+    pen: Cell<Style>,
+}
+
+impl TextCanvas {
+    /// Create a new, empty canvas of a specified width/height.
+    pub fn new(width: u16, height: u16) -> TextCanvas {
+        let size: usize = width as usize * height as usize;
+        TextCanvas {
+            width,
+            height,
+            cells: RefCell::new(vec![' '; size]),
+            styles: RefCell::new(vec![Style::PLAIN; size]),
+            pen: Cell::new(Style::PLAIN),
+        }
+    }
+
+    /// Constrain a point within the bounding box of this canvas.
+    fn clamp_to_canvas(&self, point: &PVec2) -> PVec2 {
+        PVec2::new(
+            min(point.x, self.width.saturating_sub(1)),
+            min(point.y, self.height.saturating_sub(1)),
+        )
+    }
+
+    /// Write a string into the frame, one line per row. Anything past the edge of the canvas is
+    /// cut off. If `transparent` is set, whitespace is skipped.
+    fn write_str(&self, content: &str, start: &PVec2, transparent: bool) {
+        let actual: PVec2 = self.clamp_to_canvas(start);
+        let mut cells: RefMut<Vec<char>> = self.cells.borrow_mut();
+        let mut styles: RefMut<Vec<Style>> = self.styles.borrow_mut();
+        for (dy, line) in content.split('\n').enumerate() {
+            for (dx, character) in line.chars().enumerate() {
+                if transparent && character.is_whitespace() { continue }
+                let (x, y) = (actual.x as usize + dx, actual.y as usize + dy);
+                if x >= self.width as usize || y >= self.height as usize { continue }
+                cells[y * self.width as usize + x] = character;
+                styles[y * self.width as usize + x] = self.pen.get();
+            }
+        }
+    }
+
+    /// Write the frame to a sink, one line per row, showing styles with the colors available at
+    /// a color depth. If `trim` is set, trailing spaces are left off each line.
+    pub fn write_to(&self, out: &mut impl Write, colors: ColorDepth, trim: bool) -> io::Result<()> {
+        for (y, line) in self.lines().iter().enumerate() {
+            let line: &str = if trim { line.trim_end() } else { line };
+            self.write_row(out, y as u16, line, colors)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Write the characters of a row to a sink, without a line break, showing the styles of its
+    /// cells with the colors available at a color depth.
+    pub fn write_row(&self, out: &mut impl Write, y: u16, line: &str, colors: ColorDepth) -> io::Result<()> {
+        // print runs of cells with the same style together
+        let mut run: String = String::new();
+        let mut run_style: Style = Style::PLAIN;
+        for (x, character) in line.chars().enumerate() {
+            let style: Style = self.style(&PVec2::new(x as u16, y));
+            if style != run_style && !run.is_empty() {
+                write!(out, "{}", run_style.content_style(colors).apply(&run))?;
+                run.clear();
+            }
+            run_style = style;
+            run.push(character);
+        }
+        write!(out, "{}", run_style.content_style(colors).apply(&run))
+    }
+}
+
+impl Canvas for TextCanvas {
+    /// A text canvas is its own frame.
+    fn frame(&self) -> &TextCanvas {
+        self
+    }
+
+    fn frame_mut(&mut self) -> &mut TextCanvas {
+        self
+    }
+
+    fn width(&self) -> u16 {
+        self.width
+    }
+
+    fn height(&self) -> u16 {
+        self.height
+    }
+
+    fn get(&self, point: &PVec2) -> char {
+        if point.x >= self.width || point.y >= self.height { return ' ' }
+        self.cells.borrow()[point.y as usize * self.width as usize + point.x as usize]
+    }
+
+    fn put(&self, character: char, point: &PVec2) {
+        if self.width == 0 || self.height == 0 { return }
+        let actual: PVec2 = self.clamp_to_canvas(point);
+        let index: usize = actual.y as usize * self.width as usize + actual.x as usize;
+        self.cells.borrow_mut()[index] = character;
+        self.styles.borrow_mut()[index] = self.pen.get();
+    }
+
+    fn put_str(&self, content: &str, start: &PVec2) {
+        self.write_str(content, start, false);
+    }
+
+    fn put_str_transparent(&self, content: &str, start: &PVec2) {
+        self.write_str(content, start, true);
+    }
+
+    fn set_style(&self, style: Style) {
+        self.pen.set(style);
+    }
+
+    fn style(&self, point: &PVec2) -> Style {
+        if point.x >= self.width || point.y >= self.height { return Style::PLAIN }
+        self.styles.borrow()[point.y as usize * self.width as usize + point.x as usize]
+    }
+
+    fn draw_scene(&self, scene: &Scene) {
+        scene.draw(self);
+    }
+
+    fn clear(&self) {
+        self.cells.borrow_mut().fill(' ');
+        self.styles.borrow_mut().fill(Style::PLAIN);
+    }
+
+    fn set_size(&mut self, width: u16, height: u16) {
+        *self = TextCanvas::new(width, height);
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let cells: Ref<Vec<char>> = self.cells.borrow();
+        cells.chunks(max(self.width, 1) as usize).map(|row| row.iter().collect()).collect()
+    }
+
+    /// Nothing to do; the contents stay in memory, and can be read with `lines()` or by
+    /// formatting the canvas.
+    fn finish(&self) {}
+}
+
+impl Display for TextCanvas {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for line in self.lines() {
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Canvas that writes its contents as lines of text to a sink, such as a file or a pipe, when
+/// finished. Trailing spaces are left off each line.
+pub struct WriteCanvas<W: Write> {
+    frame: TextCanvas,
+    sink: RefCell<W>,
+    /// Colors that can be used in the output; plain text by default.
+    colors: ColorDepth,
+}
+
+impl<W: Write> WriteCanvas<W> {
+    /// Create a new canvas of a specified width/height, that writes to a sink.
+    pub fn new(sink: W, width: u16, height: u16) -> WriteCanvas<W> {
+        WriteCanvas { frame: TextCanvas::new(width, height), sink: RefCell::new(sink), colors: ColorDepth::None }
+    }
+
+    /// Set the colors that can be used in the output. Colors are written as ANSI escape
+    /// sequences, so this is only useful for output that ends up on a terminal.
+    pub fn colors(mut self, colors: ColorDepth) -> WriteCanvas<W> {
+        self.colors = colors;
+        self
+    }
+}
+
+impl<W: Write> Canvas for WriteCanvas<W> {
+    fn frame(&self) -> &TextCanvas {
+        &self.frame
+    }
+
+    fn frame_mut(&mut self) -> &mut TextCanvas {
+        &mut self.frame
+    }
+
+    fn finish(&self) {
+        let mut sink: RefMut<W> = self.sink.borrow_mut();
+        self.frame.write_to(&mut *sink, self.colors, true).and_then(|_| sink.flush()).expect("Error writing output");
+    }
+}
@@ -0,0 +1,201 @@
+// clplot::renderer::color - colors and text styles
+//     Copyright (C) 2024  Dustin Thomas <stdio@cptlobster.dev>
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+/// Colors and text styles. Styles are kept with each cell of a canvas; canvases that can't show
+/// color (such as plain text files) ignore them.
+use std::str::FromStr;
+pub use crossterm::style::Color;
+use crossterm::style::{Attribute, ContentStyle};
+
+/// Default colors for each series, in order.
+pub const PALETTE: [Color; 6] = [
+    Color::Rgb { r: 0x4e, g: 0x79, b: 0xa7 },
+    Color::Rgb { r: 0xf2, g: 0x8e, b: 0x2b },
+    Color::Rgb { r: 0x59, g: 0xa1, b: 0x4f },
+    Color::Rgb { r: 0xe1, g: 0x57, b: 0x59 },
+    Color::Rgb { r: 0xb0, g: 0x7a, b: 0xa1 },
+    Color::Rgb { r: 0x76, g: 0xb7, b: 0xb2 },
+];
+
+/// Colors for each series on terminals with only the 16 standard colors, in the same order as
+/// `PALETTE`. Converting `PALETTE` could give the same color twice.
+const PALETTE_16: [Color; 6] = [Color::Blue, Color::Yellow, Color::Green, Color::Red, Color::Magenta, Color::Cyan];
+
+/// Color of axis lines and ticks.
+pub const AXIS_COLOR: Color = Color::Rgb { r: 0x80, g: 0x80, b: 0x80 };
+
+/// The 16 standard terminal colors, and roughly how they look.
+const ANSI_16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0x00, 0x00, 0x00)),
+    (Color::DarkRed, (0x80, 0x00, 0x00)),
+    (Color::DarkGreen, (0x00, 0x80, 0x00)),
+    (Color::DarkYellow, (0x80, 0x80, 0x00)),
+    (Color::DarkBlue, (0x00, 0x00, 0x80)),
+    (Color::DarkMagenta, (0x80, 0x00, 0x80)),
+    (Color::DarkCyan, (0x00, 0x80, 0x80)),
+    (Color::Grey, (0xc0, 0xc0, 0xc0)),
+    (Color::DarkGrey, (0x80, 0x80, 0x80)),
+    (Color::Red, (0xff, 0x00, 0x00)),
+    (Color::Green, (0x00, 0xff, 0x00)),
+    (Color::Yellow, (0xff, 0xff, 0x00)),
+    (Color::Blue, (0x00, 0x00, 0xff)),
+    (Color::Magenta, (0xff, 0x00, 0xff)),
+    (Color::Cyan, (0x00, 0xff, 0xff)),
+    (Color::White, (0xff, 0xff, 0xff)),
+];
+
+/// Get the red, green and blue parts of a color. Named colors are given roughly how they look on
+/// most terminals, and the terminal's default color is black.
+pub fn rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb { r, g, b } => (r, g, b),
+        Color::AnsiValue(n) if n < 16 => ANSI_16[n as usize].1,
+        Color::AnsiValue(n) if n < 232 => {
+            // 6x6x6 color cube
+            let level = |i: u8| if i == 0 { 0 } else { 55 + i * 40 };
+            let n: u8 = n - 16;
+            (level(n / 36), level(n / 6 % 6), level(n % 6))
+        }
+        // grayscale ramp
+        Color::AnsiValue(n) => {
+            let level: u8 = 8 + (n - 232) * 10;
+            (level, level, level)
+        }
+        named => ANSI_16.iter().find(|(c, _)| *c == named).map(|(_, rgb)| *rgb).unwrap_or((0, 0, 0)),
+    }
+}
+
+/// How a character is drawn, besides the character itself.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct Style {
+    /// Color of the character; `None` uses the terminal's default.
+    pub foreground: Option<Color>,
+    pub bold: bool,
+}
+
+impl Style {
+    /// Plain text, in the terminal's default color.
+    pub const PLAIN: Style = Style { foreground: None, bold: false };
+
+    /// Style with a foreground color.
+    pub fn color(color: Color) -> Style {
+        Style { foreground: Some(color), bold: false }
+    }
+
+    /// Draw in bold.
+    pub fn bold(mut self) -> Style {
+        self.bold = true;
+        self
+    }
+
+    /// Convert to a crossterm style, using the colors available at a color depth.
+    pub fn content_style(&self, depth: ColorDepth) -> ContentStyle {
+        let mut style: ContentStyle = ContentStyle::new();
+        style.foreground_color = self.foreground.and_then(|color| depth.convert(color));
+        if self.bold && depth != ColorDepth::None { style.attributes.set(Attribute::Bold); }
+        style
+    }
+}
+
+/// When to use color, as chosen on the command line.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ColorChoice {
+    Never,
+    /// Only when writing to a terminal that supports color, and `NO_COLOR` isn't set.
+    Auto,
+    Always,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(ColorChoice::Never),
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            _ => Err(format!("unknown color choice \"{}\" (expected never, auto or always)", s)),
+        }
+    }
+}
+
+/// Colors that a terminal can show, from fewest to most.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
+pub enum ColorDepth {
+    /// No color at all.
+    None,
+    /// The 16 standard colors.
+    Ansi16,
+    /// The 256-color palette.
+    Ansi256,
+    /// Any 24-bit RGB color.
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// Get the colors to draw each series with at this depth.
+    pub fn palette(&self) -> Vec<Color> {
+        match self {
+            ColorDepth::Ansi16 => PALETTE_16.to_vec(),
+            _ => PALETTE.to_vec(),
+        }
+    }
+
+    /// Get the closest color to `color` that can be shown at this depth, or `None` if no color
+    /// can be shown.
+    pub fn convert(&self, color: Color) -> Option<Color> {
+        let Color::Rgb { r, g, b } = color else {
+            return (*self != ColorDepth::None).then_some(color)
+        };
+        match self {
+            ColorDepth::None => None,
+            ColorDepth::Ansi16 => ANSI_16.iter()
+                .min_by_key(|(_, (r2, g2, b2))| {
+                    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+                    d(r, *r2) + d(g, *g2) + d(b, *b2)
+                })
+                .map(|(color, _)| *color),
+            ColorDepth::Ansi256 => {
+                // nearest color in the 6x6x6 color cube, which starts at 16
+                let level = |n: u8| (n as u16 * 5 + 127) / 255;
+                Some(Color::AnsiValue((16 + 36 * level(r) + 6 * level(g) + level(b)) as u8))
+            }
+            ColorDepth::TrueColor => Some(color),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_reduced_to_the_depth() {
+        let orange: Color = Color::Rgb { r: 0xff, g: 0x80, b: 0x00 };
+        assert_eq!(ColorDepth::TrueColor.convert(orange), Some(orange));
+        assert_eq!(ColorDepth::Ansi256.convert(orange), Some(Color::AnsiValue(16 + 36 * 5 + 6 * 3)));
+        assert_eq!(ColorDepth::Ansi16.convert(Color::Rgb { r: 0xf0, g: 0x10, b: 0x10 }), Some(Color::Red));
+        assert_eq!(ColorDepth::Ansi16.convert(AXIS_COLOR), Some(Color::DarkGrey));
+        assert_eq!(ColorDepth::None.convert(Color::Red), None);
+    }
+
+    #[test]
+    fn palettes_have_distinct_colors() {
+        for depth in [ColorDepth::Ansi16, ColorDepth::Ansi256, ColorDepth::TrueColor] {
+            let colors: Vec<Option<Color>> = depth.palette().into_iter().map(|c| depth.convert(c)).collect();
+            assert!(colors.iter().enumerate().all(|(i, c)| !colors[..i].contains(c)), "{:?}", depth);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+// clplot::renderer::dots - drawing at a finer resolution than one character per cell
+//     Copyright (C) 2024  Dustin Thomas <stdio@cptlobster.dev>
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+/// Sub-cell drawing. Each cell of a canvas is split into a small grid of dots, which are drawn
+/// with Unicode characters that show several dots at once.
+use std::cell::{Ref, RefCell};
+use std::str::FromStr;
+use crate::data::{PVec2, Vec2};
+use crate::renderer::canvas::Canvas;
+
+/// First character of the Unicode Braille Patterns block; the blank pattern.
+const BRAILLE_BLANK: u32 = 0x2800;
+
+/// Bit for each dot of a Braille character, by column and row within the cell.
+const BRAILLE_BITS: [[u8; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+/// Quadrant block characters, indexed by a mask of the quadrants that are filled (top left,
+/// bottom left, top right, bottom right).
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▖', '▌', '▝', '▀', '▞', '▛', '▗', '▚', '▄', '▙', '▐', '▜', '▟', '█',
+];
+
+/// How shapes are drawn on a canvas.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum DotMode {
+    /// One marker character per cell.
+    Ascii,
+    /// Unicode Braille patterns, with 2x4 dots per cell.
+    Braille,
+    /// Unicode half blocks, with the top and bottom half of each cell filled separately. Gives
+    /// solid-looking shapes, with twice the vertical resolution.
+    HalfBlock,
+    /// Unicode quadrant blocks, with 2x2 dots per cell.
+    Quadrant,
+}
+
+impl DotMode {
+    /// Get the number of dots across and down each cell.
+    pub fn cell_size(&self) -> (u16, u16) {
+        match self {
+            DotMode::Ascii => (1, 1),
+            DotMode::Braille => (2, 4),
+            DotMode::HalfBlock => (1, 2),
+            DotMode::Quadrant => (2, 2),
+        }
+    }
+
+    /// Check if this mode needs Unicode characters.
+    pub fn is_unicode(&self) -> bool {
+        *self != DotMode::Ascii
+    }
+
+    /// Get a character that stands for shapes drawn in this mode with a marker, such as in a
+    /// legend: the marker itself in ASCII, or else a cell full of dots.
+    pub fn symbol(&self, marker: char) -> char {
+        let (across, down) = self.cell_size();
+        self.character(((1u16 << (across * down)) - 1) as u8).unwrap_or(marker)
+    }
+
+    /// Get the character showing the dots that are set in a cell, from the bits of `mask`
+    /// (column by column, top to bottom), or `None` if no dots are set.
+    fn character(&self, mask: u8) -> Option<char> {
+        if mask == 0 { return None }
+        match self {
+            DotMode::Ascii => None,
+            DotMode::Braille => {
+                let bits: u8 = (0..8).filter(|i| mask & (1 << i) != 0)
+                    .map(|i| BRAILLE_BITS[i / 4][i % 4])
+                    .fold(0, |a, b| a | b);
+                char::from_u32(BRAILLE_BLANK + bits as u32)
+            }
+            // the half blocks are the quadrants with both columns filled
+            DotMode::HalfBlock => Some(QUADRANTS[(mask | mask << 2) as usize & 0xf]),
+            DotMode::Quadrant => Some(QUADRANTS[mask as usize & 0xf]),
+        }
+    }
+
+    /// Get the dots shown by a character drawn in this mode, as a mask like the one given to
+    /// `character()`.
+    fn mask(&self, character: char) -> u8 {
+        match self {
+            DotMode::Ascii => 0,
+            DotMode::Braille => {
+                let code: u32 = character as u32;
+                if !(BRAILLE_BLANK..BRAILLE_BLANK + 0x100).contains(&code) { return 0 }
+                let bits: u8 = (code - BRAILLE_BLANK) as u8;
+                (0..8).filter(|i| bits & BRAILLE_BITS[i / 4][i % 4] != 0).fold(0, |mask, i| mask | (1 << i))
+            }
+            DotMode::HalfBlock => match character {
+                '▀' => 0b01,
+                '▄' => 0b10,
+                '█' => 0b11,
+                _ => 0,
+            },
+            DotMode::Quadrant => QUADRANTS.iter().position(|q| *q == character).unwrap_or(0) as u8,
+        }
+    }
+}
+
+impl FromStr for DotMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(DotMode::Ascii),
+            "braille" => Ok(DotMode::Braille),
+            "half-block" => Ok(DotMode::HalfBlock),
+            "quadrant" => Ok(DotMode::Quadrant),
+            _ => Err(format!("unknown marker mode \"{}\" (expected ascii, braille, half-block or quadrant)", s)),
+        }
+    }
+}
+
+/// A grid of dots covering a canvas. Shapes are drawn into the grid, and then the grid is drawn
+/// onto the canvas; only cells with dots set are changed.
+pub struct Dots {
+    mode: DotMode,
+    /// Size of the grid, in dots.
+    width: u16,
+    height: u16,
+    /// Dots that are set, row by row from the top.
+    set: RefCell<Vec<bool>>,
+}
+
+impl Dots {
+    /// Create an empty grid of dots covering a canvas.
+    pub fn new(canvas: &dyn Canvas, mode: DotMode) -> Dots {
+        let (across, down) = mode.cell_size();
+        let width: u16 = canvas.width() * across;
+        let height: u16 = canvas.height() * down;
+        Dots { mode, width, height, set: RefCell::new(vec![false; width as usize * height as usize]) }
+    }
+
+    /// Convert a position in plot area coordinates (where whole numbers are the centers of
+    /// cells) to the dot it falls in.
+    fn to_dot(&self, point: Vec2) -> (i32, i32) {
+        let (across, down) = self.mode.cell_size();
+        (((point.x + 0.5) * across as f32).floor() as i32, ((point.y + 0.5) * down as f32).floor() as i32)
+    }
+
+    /// Set a single dot, if it is inside the grid.
+    fn set_dot(&self, x: i32, y: i32) {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 { return }
+        self.set.borrow_mut()[y as usize * self.width as usize + x as usize] = true;
+    }
+
+    /// Set the dot at a position in plot area coordinates.
+    pub fn point(&self, point: Vec2) {
+        let (x, y) = self.to_dot(point);
+        self.set_dot(x, y);
+    }
+
+    /// Set every dot along a line between two positions in plot area coordinates.
+    pub fn line(&self, start: Vec2, end: Vec2) {
+        let (mut px, mut py) = self.to_dot(start);
+        let (end_x, end_y) = self.to_dot(end);
+        // walk the line one dot at a time (Bresenham's algorithm)
+        let dx: i32 = (end_x - px).abs();
+        let dy: i32 = (end_y - py).abs();
+        let step_x: i32 = (end_x - px).signum();
+        let step_y: i32 = (end_y - py).signum();
+        let mut err: i32 = dx - dy;
+        loop {
+            self.set_dot(px, py);
+            if px == end_x && py == end_y { break }
+            let e2: i32 = err * 2;
+            if e2 > -dy {
+                err -= dy;
+                px += step_x;
+            }
+            if e2 < dx {
+                err += dx;
+                py += step_y;
+            }
+        }
+    }
+
+    /// Set every dot with its center inside a rectangle, given by two opposite corners in plot
+    /// area coordinates.
+    pub fn rect(&self, a: Vec2, b: Vec2) {
+        let (across, down) = self.mode.cell_size();
+        // dots with their centers inside the range, with a little tolerance for rounding errors
+        let range = |a: f32, b: f32, per_cell: u16| {
+            let to_dot = |n: f32| (n + 0.5) * per_cell as f32 - 0.5;
+            let start: i32 = (to_dot(a.min(b)) - 1e-3).ceil() as i32;
+            let end: i32 = (to_dot(a.max(b)) + 1e-3).floor() as i32;
+            start..=end
+        };
+        for y in range(a.y, b.y, down) {
+            for x in range(a.x, b.x, across) {
+                self.set_dot(x, y);
+            }
+        }
+    }
+
+    /// Get the dots set in a cell, as a mask with one bit per dot, column by column.
+    fn cell_mask(&self, x: u16, y: u16) -> u8 {
+        let (across, down) = self.mode.cell_size();
+        let set: Ref<Vec<bool>> = self.set.borrow();
+        let mut mask: u8 = 0;
+        for column in 0..across {
+            for row in 0..down {
+                let (dot_x, dot_y) = (x * across + column, y * down + row);
+                if set[dot_y as usize * self.width as usize + dot_x as usize] {
+                    mask |= 1 << (column * down + row);
+                }
+            }
+        }
+        mask
+    }
+
+    /// Draw the dots onto a canvas. Cells without any dots are left alone, and dots are added to
+    /// cells that already show dots in the same mode.
+    pub fn draw(&self, canvas: &dyn Canvas) {
+        let (across, down) = self.mode.cell_size();
+        for y in 0..self.height / down {
+            for x in 0..self.width / across {
+                let mask: u8 = self.cell_mask(x, y);
+                if mask == 0 { continue }
+                let cell: PVec2 = PVec2::new(x, y);
+                let existing: u8 = self.mode.mask(canvas.get(&cell));
+                if let Some(character) = self.mode.character(mask | existing) {
+                    canvas.put(character, &cell);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::canvas::TextCanvas;
+
+    #[test]
+    fn braille_characters() {
+        assert_eq!(DotMode::Braille.character(0), None);
+        // top left dot, and the full left column
+        assert_eq!(DotMode::Braille.character(0x01), Some('⠁'));
+        assert_eq!(DotMode::Braille.character(0x0f), Some('⡇'));
+        assert_eq!(DotMode::Braille.character(0xff), Some('⣿'));
+        assert!((0..=255).all(|mask| DotMode::Braille.mask(DotMode::Braille.character(mask).unwrap_or(' ')) == mask));
+    }
+
+    #[test]
+    fn block_characters() {
+        assert_eq!(DotMode::HalfBlock.character(0b01), Some('▀'));
+        assert_eq!(DotMode::HalfBlock.character(0b10), Some('▄'));
+        assert_eq!(DotMode::Quadrant.character(0b1001), Some('▚'));
+        assert!((0..4).all(|mask| DotMode::HalfBlock.mask(DotMode::HalfBlock.character(mask).unwrap_or(' ')) == mask));
+        assert!((0..16).all(|mask| DotMode::Quadrant.mask(DotMode::Quadrant.character(mask).unwrap_or(' ')) == mask));
+    }
+
+    #[test]
+    fn rects_fill_dots_with_centers_inside() {
+        let canvas: TextCanvas = TextCanvas::new(3, 2);
+        let dots: Dots = Dots::new(&canvas, DotMode::Quadrant);
+        // from the middle of the first cell to the middle of the last
+        dots.rect(Vec2::new(0.0, 0.0), Vec2::new(2.0, 1.0));
+        dots.draw(&canvas);
+        assert_eq!(canvas.lines(), vec!["▗▄▖", "▝▀▘"]);
+    }
+
+    #[test]
+    fn dots_within_a_cell() {
+        let canvas: TextCanvas = TextCanvas::new(2, 1);
+        let dots: Dots = Dots::new(&canvas, DotMode::Braille);
+        // whole numbers are cell centers; the left half of the first cell, top to bottom
+        dots.line(Vec2::new(-0.25, -0.375), Vec2::new(-0.25, 0.375));
+        dots.point(Vec2::new(1.25, 0.375));
+        dots.draw(&canvas);
+        assert_eq!(canvas.lines(), vec!["⡇⢀"]);
+    }
+
+    #[test]
+    fn dots_are_added_to_existing_dots() {
+        let canvas: TextCanvas = TextCanvas::new(3, 1);
+        canvas.put_str("x⠁", &PVec2::new(0, 0));
+        let dots: Dots = Dots::new(&canvas, DotMode::Braille);
+        dots.line(Vec2::new(-0.25, 0.375), Vec2::new(1.25, 0.375));
+        dots.draw(&canvas);
+        assert_eq!(canvas.lines(), vec!["⣀⣁ "]);
+    }
+}
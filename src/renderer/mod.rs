@@ -17,5 +17,12 @@
 /// The renderer API directly manipulates stdout to draw shapes, and utilizes ANSI escape sequences
 /// to handle drawing in arbitrary locations and colors.
 pub mod terminal;
+pub mod canvas;
+pub mod color;
+pub mod dots;
 pub mod plot;
-pub mod shapes;
\ No newline at end of file
+pub mod raster;
+pub mod scene;
+pub mod screen;
+pub mod shapes;
+pub mod svg;
\ No newline at end of file
@@ -17,12 +17,15 @@
 /// drawn in by other utilities) and structures for basic shapes.
 use std::cmp::{max, min};
 use std::io::{Write, stdout, Stdout};
-use crossterm::{cursor::{RestorePosition, SavePosition, MoveDown, MoveRight, MoveUp},
-                queue, QueueableCommand, style::{Print}};
-use tailcall::tailcall;
+use crossterm::{cursor::{RestorePosition, SavePosition, MoveDown, MoveUp},
+                queue, style::{Print}, terminal::{Clear, ClearType}};
 use crate::data::PVec2;
+use crate::renderer::canvas::{Canvas, TextCanvas};
+use crate::renderer::color::ColorDepth;
+use crate::renderer::scene::Scene;
 
-/// Basic plot object.
+/// Basic plot object; a canvas on the terminal. Drawing happens in an in-memory frame, which is
+/// written to the terminal all at once by `render()` or `finish()`.
 pub struct Plot {
     pub width: u16,
     pub height: u16,
@@ -30,14 +33,12 @@ pub struct Plot {
     pub x_max: u16,
     pub y_min: u16,
     pub y_max: u16,
+    frame: TextCanvas,
+    /// Colors that can be used when writing the frame to the terminal.
+    colors: ColorDepth,
 }
 
 impl Plot {
-    /// Cut off the end of a string if it is too long.
-    fn clip(input: &str, max_len: u16) -> &str {
-        if (input.len() as u16) <= max_len { input } else { input.split_at(max_len as usize).0 }
-    }
-
     /// Constrain a number within a range; If it falls outside the range, return the minimum or
     /// maximum value, depending on if it's smaller or larger.
     fn clamp(n: u16, lower: u16, upper: u16) -> u16 {
@@ -55,14 +56,6 @@ impl Plot {
         Self::clamp_point(point, self.x_min, self.x_max, self.y_min, self.y_max)
     }
 
-    /// Derive a point from decimal (float) values (from 0.0 - 1.0). (0.0, 0.0) corresponds to top
-    /// left, (1.0, 1.0) corresponds to bottom right.
-    pub fn derive_point_dec(&self, x: f32, y: f32) -> PVec2 {
-        let x_rd: u16 = x.round() as u16;
-        let y_rd: u16 = y.round() as u16;
-        self.clamp_to_plot(&PVec2::new(x_rd, y_rd))
-    }
-
     /// Get a point offset from the top left of the plot area.
     pub fn origin_bl(&self, x: u16, y: u16) -> PVec2 {
         self.clamp_to_plot(&PVec2::new(x, self.height - y))
@@ -72,102 +65,101 @@ impl Plot {
         self.clamp_to_plot(&PVec2::new(self.width - x, self.height - y))
     }
 
-    /// Create a new plot area of a specified width/height.
+    /// Create a new plot area of a specified width/height. Space for it is made on the terminal
+    /// right away, below the cursor.
     pub fn new(width: u16, height: u16) -> Plot {
         let mut out: Stdout = stdout();
         let nls: String = "\n".repeat(height as usize);
-        queue!(out, Print(nls), SavePosition);
+        queue!(out, Print(nls), SavePosition).expect("Error with terminal interaction");
         out.flush().expect("Error with terminal interaction");
+        Self::with_frame(width, height)
+    }
+
+    /// Create a plot area with an empty frame.
+    fn with_frame(width: u16, height: u16) -> Plot {
         Plot {
             width,
             height,
             x_min: 0,
-            x_max: width,
+            x_max: width.saturating_sub(1),
             y_min: 0,
-            y_max: height,
+            y_max: height.saturating_sub(1),
+            frame: TextCanvas::new(width, height),
+            colors: ColorDepth::None,
         }
     }
 
-    /// Resize plot to new width/height. It is recommended that you clear the plot after you
-    /// change size.
+    /// Set the colors that can be used when writing to the terminal. Without this, the plot is
+    /// written without any color.
+    pub fn colors(mut self, colors: ColorDepth) -> Plot {
+        self.colors = colors;
+        self
+    }
+
+    /// Resize plot to new width/height. The new plot starts with an empty frame, and whatever
+    /// the old one left on the terminal is erased.
     pub fn resize(&self, width: u16, height: u16) -> Plot {
         let mut out: Stdout = stdout();
         let nls: String = "\n".repeat(height as usize);
-        queue!(out, RestorePosition, MoveUp(self.height), Print(nls), SavePosition);
+        queue!(out, RestorePosition, MoveUp(self.height), Clear(ClearType::FromCursorDown), Print(nls), SavePosition).expect("Error with terminal interaction");
         out.flush().expect("Error with terminal interaction");
-        Plot {
-            width,
-            height,
-            x_min: 0,
-            x_max: width,
-            y_min: 0,
-            y_max: height,
-        }
+        Self::with_frame(width, height).colors(self.colors)
     }
 
-    /// Clear the plot area (fill the entire area with spaces).
-    pub fn clear(&self) {
+    /// Write the whole frame to the terminal.
+    pub fn render(&self) {
         let mut out: Stdout = stdout();
-        let cleared_area: String = (" ".repeat(self.width as usize) + "\n").repeat(self.height as usize);
-        queue!(out, RestorePosition, MoveUp(self.height), Print(cleared_area));
+        queue!(out, RestorePosition, MoveUp(self.height)).expect("Error with terminal interaction");
+        self.frame.write_to(&mut out, self.colors, false).expect("Error with terminal interaction");
         out.flush().expect("Error with terminal interaction");
     }
+}
 
-    /// Place a character at a location on the plot area.
-    pub fn put(&self, character: char, point: &PVec2) {
-        let actual : PVec2 = self.clamp_to_plot(point);
-        let mut out: Stdout = stdout();
-        queue!(out, RestorePosition, MoveUp(self.height - actual.y), MoveRight(actual.x), Print(character));
-        out.flush().expect("Error with terminal interaction");
+impl Canvas for Plot {
+    fn frame(&self) -> &TextCanvas {
+        &self.frame
+    }
+
+    fn frame_mut(&mut self) -> &mut TextCanvas {
+        &mut self.frame
+    }
+
+    fn width(&self) -> u16 {
+        self.width
+    }
+
+    fn height(&self) -> u16 {
+        self.height
+    }
+
+    fn put(&self, character: char, point: &PVec2) {
+        self.frame.put(character, &self.clamp_to_plot(point));
     }
 
     /// Print a string on the plot area. Note that whitespace will overwrite existing content; You
     /// can use `put_str_transparent()` instead if you want to ignore whitespace.
-    pub fn put_str(&self, content: &str, start: &PVec2) {
-        let mut out: Stdout = stdout();
-        let actual : PVec2 = self.clamp_to_plot(start);
-        queue!(out, RestorePosition, MoveUp(self.height - actual.y), MoveRight(actual.x));
-        let lines = content.split("\n");
-        for line in lines {
-            queue!(out, Print(Self::clip(line, self.width - actual.x)), Print("\n"), MoveRight(actual.x));
-        }
-        out.flush().expect("Error with terminal interaction");
+    fn put_str(&self, content: &str, start: &PVec2) {
+        self.frame.put_str(content, &self.clamp_to_plot(start));
     }
 
-    /// Helper function for `put_str_transparent()`.
-    #[tailcall]
-    fn consume_line(out: &mut Stdout, line: &str) {
-        if line.len() == 0 { return }
-        let Some((left, right)) = line.find(|a: char| { a.is_whitespace() }).map(|i| line.split_at(i)) else {
-            out.queue(Print(line)).expect("Error with terminal interaction");
-            return
-        };
-        out.queue(Print(left)).expect("Error with terminal interaction");
-        let Some((l2, r2)) = right.find(|a: char| { !a.is_whitespace() }).map(|i| right.split_at(i)) else {
-            return
-        };
-        out.queue(MoveRight(l2.len() as u16)).expect("Error with terminal interaction");
-        Self::consume_line(out, r2)
-    }
-
-    /// Put a string on the plot area. Whitespace will not overwrite existing content.
-    pub fn put_str_transparent(&self, content: &str, start: &PVec2) {
-        let mut out: Stdout = stdout();
-        let actual : PVec2 = self.clamp_to_plot(start);
-        queue!(out, RestorePosition, MoveUp(self.height - actual.y), MoveRight(actual.x));
-        let lines = content.split("\n");
-        for line in lines {
-            Self::consume_line(&mut out, Self::clip(line, self.width - actual.x));
-            queue!(out, Print("\n"), MoveRight(actual.x));
-        }
-        out.flush().expect("Error with terminal interaction");
+    fn put_str_transparent(&self, content: &str, start: &PVec2) {
+        self.frame.put_str_transparent(content, &self.clamp_to_plot(start));
     }
 
-    /// Run this when you are done with the plot; This will position the cursor on the line below,
-    /// so that the plot remains visible.
-    pub fn finish(&self) {
+    fn draw_scene(&self, scene: &Scene) {
+        scene.draw(self);
+    }
+
+    fn set_size(&mut self, width: u16, height: u16) {
+        *self = self.resize(width, height);
+    }
+
+    /// Run this when you are done with the plot; This will write the frame to the terminal and
+    /// position the cursor on the line below, so that the plot remains visible.
+    fn finish(&self) {
+        self.render();
         let mut out: Stdout = stdout();
-        queue!(out, RestorePosition, MoveDown(1));
+        queue!(out, RestorePosition, MoveDown(1)).expect("Error with terminal interaction");
         out.flush().expect("Error with terminal interaction");
     }
-}
\ No newline at end of file
+}
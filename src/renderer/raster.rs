@@ -0,0 +1,360 @@
+// clplot::renderer::raster - bitmap image output
+//     Copyright (C) 2024  Dustin Thomas <stdio@cptlobster.dev>
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+/// Bitmap output. Scenes are drawn into an image of any size in pixels, using the same layout as
+/// on the terminal: each cell becomes a box of pixels. Text uses a small built-in bitmap font, so
+/// no fonts or external tools are needed.
+use std::cell::{RefCell, RefMut};
+use std::io::{self, Error, Write};
+use std::str::FromStr;
+use png::{BitDepth, ColorType, Encoder};
+use crate::data::{PVec2, Vec2};
+use crate::renderer::canvas::{Canvas, TextCanvas};
+use crate::renderer::color::{rgb, Color, Style};
+use crate::renderer::scene::{Element, Scene, Shape};
+
+/// Size of each glyph of the font, in pixels.
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+
+/// Glyphs for the printable ASCII characters (space to `~`). Each glyph is five columns from left
+/// to right, with the top row in the lowest bit.
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5f, 0x00, 0x00], [0x00, 0x07, 0x00, 0x07, 0x00],
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], [0x24, 0x2a, 0x7f, 0x2a, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x55, 0x22, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00], [0x00, 0x1c, 0x22, 0x41, 0x00],
+    [0x00, 0x41, 0x22, 0x1c, 0x00], [0x08, 0x2a, 0x1c, 0x2a, 0x08], [0x08, 0x08, 0x3e, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], [0x00, 0x60, 0x60, 0x00, 0x00],
+    [0x20, 0x10, 0x08, 0x04, 0x02], [0x3e, 0x51, 0x49, 0x45, 0x3e], [0x00, 0x42, 0x7f, 0x40, 0x00],
+    [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4b, 0x31], [0x18, 0x14, 0x12, 0x7f, 0x10],
+    [0x27, 0x45, 0x45, 0x45, 0x39], [0x3c, 0x4a, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1e], [0x00, 0x36, 0x36, 0x00, 0x00],
+    [0x00, 0x56, 0x36, 0x00, 0x00], [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06], [0x32, 0x49, 0x79, 0x41, 0x3e],
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], [0x7f, 0x49, 0x49, 0x49, 0x36], [0x3e, 0x41, 0x41, 0x41, 0x22],
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], [0x7f, 0x49, 0x49, 0x49, 0x41], [0x7f, 0x09, 0x09, 0x09, 0x01],
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], [0x7f, 0x08, 0x08, 0x08, 0x7f], [0x00, 0x41, 0x7f, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3f, 0x01], [0x7f, 0x08, 0x14, 0x22, 0x41], [0x7f, 0x40, 0x40, 0x40, 0x40],
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], [0x7f, 0x04, 0x08, 0x10, 0x7f], [0x3e, 0x41, 0x41, 0x41, 0x3e],
+    [0x7f, 0x09, 0x09, 0x09, 0x06], [0x3e, 0x41, 0x51, 0x21, 0x5e], [0x7f, 0x09, 0x19, 0x29, 0x46],
+    [0x46, 0x49, 0x49, 0x49, 0x31], [0x01, 0x01, 0x7f, 0x01, 0x01], [0x3f, 0x40, 0x40, 0x40, 0x3f],
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], [0x3f, 0x40, 0x38, 0x40, 0x3f], [0x63, 0x14, 0x08, 0x14, 0x63],
+    [0x07, 0x08, 0x70, 0x08, 0x07], [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x7f, 0x41, 0x41, 0x00],
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x7f, 0x00], [0x04, 0x02, 0x01, 0x02, 0x04],
+    [0x40, 0x40, 0x40, 0x40, 0x40], [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78],
+    [0x7f, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20], [0x38, 0x44, 0x44, 0x48, 0x7f],
+    [0x38, 0x54, 0x54, 0x54, 0x18], [0x08, 0x7e, 0x09, 0x01, 0x02], [0x0c, 0x52, 0x52, 0x52, 0x3e],
+    [0x7f, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7d, 0x40, 0x00], [0x20, 0x40, 0x44, 0x3d, 0x00],
+    [0x7f, 0x10, 0x28, 0x44, 0x00], [0x00, 0x41, 0x7f, 0x40, 0x00], [0x7c, 0x04, 0x18, 0x04, 0x78],
+    [0x7c, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38], [0x7c, 0x14, 0x14, 0x14, 0x08],
+    [0x08, 0x14, 0x14, 0x18, 0x7c], [0x7c, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3f, 0x44, 0x40, 0x20], [0x3c, 0x40, 0x40, 0x20, 0x7c], [0x1c, 0x20, 0x40, 0x20, 0x1c],
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], [0x44, 0x28, 0x10, 0x28, 0x44], [0x0c, 0x50, 0x50, 0x50, 0x3c],
+    [0x44, 0x64, 0x54, 0x4c, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00], [0x00, 0x00, 0x7f, 0x00, 0x00],
+    [0x00, 0x41, 0x36, 0x08, 0x00], [0x08, 0x04, 0x08, 0x10, 0x08],
+];
+
+/// Get the glyph for a character. Characters the font doesn't have are drawn as `?`.
+fn glyph(character: char) -> &'static [u8; 5] {
+    let index: usize = (character as usize).wrapping_sub(' ' as usize);
+    FONT.get(index).unwrap_or(&FONT['?' as usize - ' ' as usize])
+}
+
+/// Size of an image, in pixels.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct ImageSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl FromStr for ImageSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("invalid image size \"{}\" (expected WIDTHxHEIGHT, such as 960x640)", s);
+        let (width, height) = s.split_once('x').ok_or_else(error)?;
+        let width: u32 = width.trim().parse().map_err(|_| error())?;
+        let height: u32 = height.trim().parse().map_err(|_| error())?;
+        if width == 0 || height == 0 { return Err(error()) }
+        Ok(ImageSize { width, height })
+    }
+}
+
+/// An RGB image, kept in memory.
+pub struct Bitmap {
+    pub width: u32,
+    pub height: u32,
+    /// Red, green and blue bytes of each pixel, row by row from the top.
+    data: Vec<u8>,
+}
+
+impl Bitmap {
+    /// Create a new image of a specified width/height, filled with white.
+    pub fn new(width: u32, height: u32) -> Bitmap {
+        Bitmap { width, height, data: vec![0xff; width as usize * height as usize * 3] }
+    }
+
+    /// Get the color of a pixel. Pixels outside the image are white.
+    #[cfg(test)]
+    pub fn get(&self, x: u32, y: u32) -> (u8, u8, u8) {
+        if x >= self.width || y >= self.height { return (0xff, 0xff, 0xff) }
+        let i: usize = (y as usize * self.width as usize + x as usize) * 3;
+        (self.data[i], self.data[i + 1], self.data[i + 2])
+    }
+
+    /// Set the color of a pixel, if it is inside the image.
+    fn set(&mut self, x: i64, y: i64, (r, g, b): (u8, u8, u8)) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 { return }
+        let i: usize = (y as usize * self.width as usize + x as usize) * 3;
+        self.data[i..i + 3].copy_from_slice(&[r, g, b]);
+    }
+
+    /// Fill every pixel with its center inside a rectangle, given by two opposite corners.
+    pub fn fill_rect(&mut self, (x1, y1): (f32, f32), (x2, y2): (f32, f32), color: (u8, u8, u8)) {
+        let range = |a: f32, b: f32| (a.min(b) - 0.5).ceil() as i64..=(a.max(b) - 0.5).floor() as i64;
+        for y in range(y1, y2) {
+            for x in range(x1, x2) {
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    /// Draw a line of a certain thickness between two points.
+    pub fn line(&mut self, start: (f32, f32), end: (f32, f32), thickness: f32, color: (u8, u8, u8)) {
+        let half: f32 = thickness / 2.0;
+        // stamp a square at every half pixel along the line
+        let steps: usize = ((end.0 - start.0).abs().max((end.1 - start.1).abs()) * 2.0).ceil() as usize;
+        for i in 0..=steps {
+            let t: f32 = if steps == 0 { 0.0 } else { i as f32 / steps as f32 };
+            let (x, y) = (start.0 + (end.0 - start.0) * t, start.1 + (end.1 - start.1) * t);
+            self.fill_rect((x - half, y - half), (x + half, y + half), color);
+        }
+    }
+
+    /// Draw a circle, either filled in or as an outline of a certain thickness.
+    pub fn circle(&mut self, (cx, cy): (f32, f32), radius: f32, outline: Option<f32>, color: (u8, u8, u8)) {
+        let inner: f32 = outline.map(|thickness| radius - thickness).unwrap_or(0.0).max(0.0);
+        for y in (cy - radius).floor() as i64..=(cy + radius).ceil() as i64 {
+            for x in (cx - radius).floor() as i64..=(cx + radius).ceil() as i64 {
+                let distance: f32 = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+                if distance <= radius && distance >= inner { self.set(x, y, color); }
+            }
+        }
+    }
+
+    /// Draw a character of the built-in font, with its top left corner at a pixel, with each
+    /// pixel of the glyph scaled up to a square.
+    pub fn character(&mut self, character: char, (x, y): (i64, i64), scale: u32, bold: bool, color: (u8, u8, u8)) {
+        let scale: i64 = scale as i64;
+        for (column, bits) in glyph(character).iter().enumerate() {
+            for row in 0..GLYPH_HEIGHT as i64 {
+                if bits & (1 << row) == 0 { continue }
+                // bold text is drawn twice, one pixel apart
+                for offset in 0..=bold as i64 {
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.set(x + column as i64 * scale + dx + offset, y + row * scale + dy, color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Encode the image as a PNG file.
+    pub fn write_png(&self, sink: impl Write) -> io::Result<()> {
+        let mut encoder: Encoder<_> = Encoder::new(sink, self.width, self.height);
+        encoder.set_color(ColorType::Rgb);
+        encoder.set_depth(BitDepth::Eight);
+        let to_io = |e: png::EncodingError| Error::other(e.to_string());
+        encoder.write_header().map_err(to_io)?.write_image_data(&self.data).map_err(to_io)
+    }
+}
+
+/// Draws scenes into a bitmap, with each cell of the scene stretched over a box of pixels.
+struct Painter {
+    bitmap: Bitmap,
+    /// Size of a cell, in pixels.
+    cell: (f32, f32),
+}
+
+impl Painter {
+    /// Get the position of a point in plot area coordinates, in pixels.
+    fn pixels(&self, point: Vec2) -> (f32, f32) {
+        ((point.x + 0.5) * self.cell.0, (point.y + 0.5) * self.cell.1)
+    }
+
+    /// Get the thickness of lines, in pixels.
+    fn thickness(&self) -> f32 {
+        (self.cell.0.min(self.cell.1) / 6.0).round().max(1.0)
+    }
+
+    /// Draw a point marker. The shape depends on the symbol the point is drawn with in character
+    /// mode.
+    fn marker(&mut self, symbol: char, (x, y): (f32, f32), color: (u8, u8, u8)) {
+        let r: f32 = (self.cell.0.min(self.cell.1) * 0.3).max(1.5);
+        let thickness: f32 = self.thickness();
+        match symbol {
+            '+' => {
+                self.bitmap.line((x - r, y), (x + r, y), thickness, color);
+                self.bitmap.line((x, y - r), (x, y + r), thickness, color);
+            }
+            'x' => {
+                self.bitmap.line((x - r, y - r), (x + r, y + r), thickness, color);
+                self.bitmap.line((x - r, y + r), (x + r, y - r), thickness, color);
+            }
+            'o' => self.bitmap.circle((x, y), r, Some(thickness), color),
+            '#' => self.bitmap.fill_rect((x - r, y - r), (x + r, y + r), color),
+            _ => self.bitmap.circle((x, y), r, None, color),
+        }
+    }
+
+    /// Draw text starting at a cell, with one character per cell.
+    fn text(&mut self, text: &str, position: PVec2, style: &Style) {
+        let scale: u32 = ((self.cell.0 / (GLYPH_WIDTH + 1) as f32).min(self.cell.1 / (GLYPH_HEIGHT + 2) as f32) as u32).max(1);
+        let color: (u8, u8, u8) = rgb(style.foreground.unwrap_or(Color::Black));
+        for (row, line) in text.split('\n').enumerate() {
+            for (column, character) in line.chars().enumerate() {
+                if character.is_whitespace() { continue }
+                let left: f32 = (position.x as usize + column) as f32 * self.cell.0 + (self.cell.0 - (GLYPH_WIDTH * scale) as f32) / 2.0;
+                let top: f32 = (position.y as usize + row) as f32 * self.cell.1 + (self.cell.1 - (GLYPH_HEIGHT * scale) as f32) / 2.0;
+                self.bitmap.character(character, (left.round() as i64, top.round() as i64), scale, style.bold, color);
+            }
+        }
+    }
+
+    fn draw(&mut self, scene: &Scene) {
+        for element in scene.elements() {
+            match element {
+                Element::Text { text, position, style } => self.text(text, *position, style),
+                Element::Shapes { shapes, symbol, style, .. } => {
+                    let color: (u8, u8, u8) = rgb(style.foreground.unwrap_or(Color::Black));
+                    for shape in shapes {
+                        match *shape {
+                            Shape::Line(start, end) => {
+                                let thickness: f32 = self.thickness();
+                                self.bitmap.line(self.pixels(start), self.pixels(end), thickness, color);
+                            }
+                            Shape::Point(position) => self.marker(*symbol, self.pixels(position), color),
+                            Shape::Rect(a, b) => self.bitmap.fill_rect(self.pixels(a), self.pixels(b), color),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draw a scene into an image of a specified size.
+pub fn render(scene: &Scene, size: ImageSize) -> Bitmap {
+    let cell: (f32, f32) = (size.width as f32 / scene.width.max(1) as f32, size.height as f32 / scene.height.max(1) as f32);
+    let mut painter: Painter = Painter { bitmap: Bitmap::new(size.width, size.height), cell };
+    painter.draw(scene);
+    painter.bitmap
+}
+
+/// Canvas that writes the scenes drawn on it to a sink as a PNG image, when finished. Characters
+/// drawn directly on the canvas, rather than as part of a scene, are left out.
+pub struct PngCanvas<W: Write> {
+    frame: TextCanvas,
+    scene: RefCell<Scene>,
+    size: ImageSize,
+    sink: RefCell<W>,
+}
+
+impl<W: Write> PngCanvas<W> {
+    /// Create a new canvas of a specified width/height in cells, that writes an image of a
+    /// specified size in pixels to a sink.
+    pub fn new(sink: W, width: u16, height: u16, size: ImageSize) -> PngCanvas<W> {
+        PngCanvas {
+            frame: TextCanvas::new(width, height),
+            scene: RefCell::new(Scene::new(width, height)),
+            size,
+            sink: RefCell::new(sink),
+        }
+    }
+}
+
+impl<W: Write> Canvas for PngCanvas<W> {
+    fn frame(&self) -> &TextCanvas {
+        &self.frame
+    }
+
+    fn frame_mut(&mut self) -> &mut TextCanvas {
+        &mut self.frame
+    }
+
+    /// The scene is kept as it is, and drawn into the image when the canvas is finished.
+    fn draw_scene(&self, scene: &Scene) {
+        self.scene.borrow_mut().extend(scene);
+    }
+
+    fn clear(&self) {
+        self.frame.clear();
+        let (width, height) = (self.frame.width, self.frame.height);
+        *self.scene.borrow_mut() = Scene::new(width, height);
+    }
+
+    fn set_size(&mut self, width: u16, height: u16) {
+        self.frame.set_size(width, height);
+        *self.scene.borrow_mut() = Scene::new(width, height);
+    }
+
+    fn finish(&self) {
+        let mut sink: RefMut<W> = self.sink.borrow_mut();
+        render(&self.scene.borrow(), self.size).write_png(&mut *sink).and_then(|_| sink.flush()).expect("Error writing output");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::dots::DotMode;
+
+    /// Draw a glyph as text, one row per line, with `#` for set pixels.
+    fn glyph_rows(character: char) -> Vec<String> {
+        let mut bitmap: Bitmap = Bitmap::new(GLYPH_WIDTH, GLYPH_HEIGHT);
+        bitmap.character(character, (0, 0), 1, false, (0, 0, 0));
+        (0..GLYPH_HEIGHT).map(|y| (0..GLYPH_WIDTH).map(|x| if bitmap.get(x, y) == (0, 0, 0) { '#' } else { '.' }).collect()).collect()
+    }
+
+    #[test]
+    fn font_glyphs() {
+        assert_eq!(glyph_rows('4'), vec!["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."]);
+        assert_eq!(glyph_rows('\u{2800}'), glyph_rows('?'));
+    }
+
+    #[test]
+    fn image_sizes() {
+        assert_eq!("960x640".parse::<ImageSize>(), Ok(ImageSize { width: 960, height: 640 }));
+        assert!("960".parse::<ImageSize>().is_err());
+        assert!("0x10".parse::<ImageSize>().is_err());
+    }
+
+    #[test]
+    fn scenes_are_drawn_into_pixels() {
+        let mut scene: Scene = Scene::new(4, 2);
+        scene.shapes(vec![Shape::Rect(Vec2::new(-0.5, -0.5), Vec2::new(1.5, 0.5))], '#', DotMode::Ascii, Style::color(Color::Red));
+        let bitmap: Bitmap = render(&scene, ImageSize { width: 40, height: 20 });
+        // the rectangle covers the first two cells of the top row
+        assert_eq!(bitmap.get(0, 0), (0xff, 0, 0));
+        assert_eq!(bitmap.get(19, 9), (0xff, 0, 0));
+        assert_eq!(bitmap.get(20, 9), (0xff, 0xff, 0xff));
+        assert_eq!(bitmap.get(0, 10), (0xff, 0xff, 0xff));
+        let mut png: Vec<u8> = Vec::new();
+        bitmap.write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+}
@@ -0,0 +1,120 @@
+// clplot::renderer::scene - drawings that can be rendered by any back end
+//     Copyright (C) 2024  Dustin Thomas <stdio@cptlobster.dev>
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+/// Scenes. Charts describe what they draw as a list of elements (text, lines, points and
+/// rectangles), laid out on the grid of cells of a canvas. Character canvases draw the elements
+/// with characters; other back ends can draw them exactly.
+use crate::data::{PVec2, Vec2};
+use crate::renderer::canvas::Canvas;
+use crate::renderer::color::Style;
+use crate::renderer::dots::{DotMode, Dots};
+use crate::renderer::shapes::{Line, Point, Rect};
+
+/// Size of a cell, in pixels, when a scene is drawn as an image. SVG output always uses this size;
+/// PNG output stretches cells to fill the image, and uses it to pick the number of cells when
+/// that isn't given.
+pub const CELL_WIDTH: u32 = 12;
+pub const CELL_HEIGHT: u32 = 24;
+
+/// A shape, in plot area coordinates (where whole numbers are the centers of cells).
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Shape {
+    /// A line between two points.
+    Line(Vec2, Vec2),
+    /// A single point.
+    Point(Vec2),
+    /// A filled rectangle, between two opposite corners.
+    Rect(Vec2, Vec2),
+}
+
+/// Something drawn in a scene.
+#[derive(PartialEq, Clone, Debug)]
+pub enum Element {
+    /// Text, starting at a cell. Each line of the text goes on its own row.
+    Text { text: String, position: PVec2, style: Style },
+    /// A group of shapes, drawn with a symbol or with dots. Shapes in a group drawn with dots
+    /// share one grid of dots.
+    Shapes { shapes: Vec<Shape>, symbol: char, mode: DotMode, style: Style },
+}
+
+/// A drawing on a grid of cells, made up of elements that are drawn in order.
+pub struct Scene {
+    pub width: u16,
+    pub height: u16,
+    elements: Vec<Element>,
+}
+
+impl Scene {
+    /// Create an empty scene of a specified width/height, in cells.
+    pub fn new(width: u16, height: u16) -> Scene {
+        Scene { width, height, elements: Vec::new() }
+    }
+
+    /// Add some text, starting at a cell.
+    pub fn text(&mut self, text: &str, position: PVec2, style: Style) {
+        self.elements.push(Element::Text { text: text.to_string(), position, style });
+    }
+
+    /// Add a group of shapes. Empty groups are left out.
+    pub fn shapes(&mut self, shapes: Vec<Shape>, symbol: char, mode: DotMode, style: Style) {
+        if shapes.is_empty() { return }
+        self.elements.push(Element::Shapes { shapes, symbol, mode, style });
+    }
+
+    /// Add every element of another scene, after the elements already in this one.
+    pub fn extend(&mut self, other: &Scene) {
+        self.elements.extend(other.elements.iter().cloned());
+    }
+
+    /// Get the elements of the scene, in the order they are drawn.
+    pub fn elements(&self) -> &[Element] {
+        &self.elements
+    }
+
+    /// Draw the scene with characters on a canvas.
+    pub fn draw(&self, canvas: &dyn Canvas) {
+        for element in &self.elements {
+            match element {
+                Element::Text { text, position, style } => {
+                    canvas.set_style(*style);
+                    canvas.put_str(text, position);
+                }
+                Element::Shapes { shapes, symbol, mode: DotMode::Ascii, style } => {
+                    canvas.set_style(*style);
+                    for shape in shapes {
+                        match *shape {
+                            Shape::Line(start, end) => Line::between(start, end, *symbol).draw(canvas),
+                            Shape::Point(position) => Point::at(position, *symbol).draw(canvas),
+                            Shape::Rect(a, b) => Rect::covering(a, b, *symbol).fill().draw(canvas),
+                        }
+                    }
+                }
+                Element::Shapes { shapes, mode, style, .. } => {
+                    canvas.set_style(*style);
+                    let dots: Dots = Dots::new(canvas, *mode);
+                    for shape in shapes {
+                        match *shape {
+                            Shape::Line(start, end) => dots.line(start, end),
+                            Shape::Point(position) => dots.point(position),
+                            Shape::Rect(a, b) => Rect::covering(a, b, ' ').fill().draw_dots(&dots),
+                        }
+                    }
+                    dots.draw(canvas);
+                }
+            }
+        }
+        canvas.set_style(Style::PLAIN);
+    }
+}
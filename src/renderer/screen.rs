@@ -0,0 +1,75 @@
+// clplot::renderer::screen - full-screen drawing on the terminal
+//     Copyright (C) 2024  Dustin Thomas <stdio@cptlobster.dev>
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+/// Full-screen drawing. Unlike a plot, which is drawn in place below the cursor, a screen takes
+/// over the whole terminal, on the alternate screen and in raw mode, so that it can be redrawn
+/// freely and read key presses and mouse events as they happen. The terminal is put back the way it
+/// was when the screen is dropped.
+use std::io::{self, stdout, Stdout, Write};
+use crossterm::{cursor::{Hide, MoveTo, Show}, execute, queue};
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use crate::renderer::canvas::{Canvas, TextCanvas};
+use crate::renderer::color::ColorDepth;
+
+/// Canvas covering the whole terminal. Drawing happens in an in-memory frame, which is written to
+/// the terminal all at once by `render()` or `finish()`.
+pub struct Screen {
+    frame: TextCanvas,
+    /// Colors that can be used when writing the frame to the terminal.
+    colors: ColorDepth,
+}
+
+impl Screen {
+    /// Switch to the alternate screen and raw mode, start reporting mouse events, and create a
+    /// screen of a specified width/height (normally the size of the terminal).
+    pub fn new(width: u16, height: u16, colors: ColorDepth) -> io::Result<Screen> {
+        enable_raw_mode()?;
+        execute!(stdout(), EnterAlternateScreen, Hide, EnableMouseCapture)?;
+        Ok(Screen { frame: TextCanvas::new(width, height), colors })
+    }
+
+    /// Write the whole frame to the terminal.
+    pub fn render(&self) {
+        let mut out: Stdout = stdout();
+        for (y, line) in self.frame.lines().iter().enumerate() {
+            queue!(out, MoveTo(0, y as u16)).expect("Error with terminal interaction");
+            self.frame.write_row(&mut out, y as u16, line, self.colors).expect("Error with terminal interaction");
+        }
+        out.flush().expect("Error with terminal interaction");
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), DisableMouseCapture, Show, LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+    }
+}
+
+impl Canvas for Screen {
+    fn frame(&self) -> &TextCanvas {
+        &self.frame
+    }
+
+    fn frame_mut(&mut self) -> &mut TextCanvas {
+        &mut self.frame
+    }
+
+    /// Write the frame to the terminal. The screen stays up until it is dropped.
+    fn finish(&self) {
+        self.render();
+    }
+}
@@ -13,34 +13,71 @@
 //     You should have received a copy of the GNU General Public License
 //     along with this program.  If not, see <https://www.gnu.org/licenses/>.
 /// Common shapes and drawing code.
-
 /// Basic shapes.
-use crate::renderer::plot::Plot;
+use std::str::FromStr;
+use crate::renderer::canvas::Canvas;
+use crate::renderer::dots::Dots;
 use crate::data::{Vec2, PVec2};
 
-/// The "view box" provides an easy way to constrain shapes to a specific portion of the plot area.
-pub struct ViewBox {
-    plot: Plot,
-    position: PVec2,
-    size: PVec2,
+/// How values are spread along an axis of a scaled view box.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Scale {
+    Linear,
+    /// Base 10 logarithm.
+    Log10,
+    /// Base 2 logarithm.
+    Log2,
+    /// Natural logarithm.
+    Ln,
 }
 
-impl ViewBox {
-    fn clamp(n: u16, lower: u16, upper: u16) -> u16 {
-        lower.max(n.min(upper))
+impl Scale {
+    /// Get the base of a logarithmic scale.
+    pub fn base(&self) -> Option<f32> {
+        match self {
+            Scale::Linear => None,
+            Scale::Log10 => Some(10.0),
+            Scale::Log2 => Some(2.0),
+            Scale::Ln => Some(std::f32::consts::E),
+        }
     }
-    
-    fn clamp_point(point: PVec2, x_min: u16, x_max: u16, y_min: u16, y_max: u16) -> PVec2 {
-        PVec2::new(Self::clamp(point.x, x_min, x_max), Self::clamp(point.y, y_min, y_max))
+
+    /// Transform a value onto this scale.
+    pub fn apply(&self, n: f32) -> f32 {
+        match self {
+            Scale::Linear => n,
+            Scale::Log10 => n.log10(),
+            Scale::Log2 => n.log2(),
+            Scale::Ln => n.ln(),
+        }
     }
 
-    fn clamp_to_plot(&self, point: PVec2) -> PVec2 {
-        Self::clamp_point(point, 0, self.size.x, 0, self.size.y)
+    /// Check if a value can be placed on this scale. Logarithmic scales only cover values greater
+    /// than zero.
+    pub fn contains(&self, n: f32) -> bool {
+        *self == Scale::Linear || n > 0.0
     }
 
-    /// Translates relative coordinates to absolute coordinates on the parent plot
-    pub fn translate_to_plot(&self, point: PVec2) -> PVec2 {
-        self.clamp_to_plot(point + self.position)
+    /// Transform a value on this scale back to the original value.
+    pub fn invert(&self, n: f32) -> f32 {
+        match self.base() {
+            None => n,
+            Some(base) => base.powf(n),
+        }
+    }
+}
+
+impl FromStr for Scale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Scale::Linear),
+            "log" | "log10" => Ok(Scale::Log10),
+            "log2" => Ok(Scale::Log2),
+            "ln" => Ok(Scale::Ln),
+            _ => Err(format!("unknown scale \"{}\" (expected linear, log10, log2 or ln)", s)),
+        }
     }
 }
 
@@ -48,16 +85,31 @@ impl ViewBox {
 /// - It can constrain shapes to a specific portion of the plot area
 /// - It allows for converting from arbitrary scales to plot coordinate values.
 pub struct ScaledViewBox {
-    plot: Plot,
     position: PVec2,
     size: PVec2,
     x_min: f32,
     x_max: f32,
     y_min: f32,
     y_max: f32,
+    x_scale: Scale,
+    y_scale: Scale,
 }
 
 impl ScaledViewBox {
+    /// Create a new scaled view box. `position` and `size` are in plot coordinates; the minimum
+    /// and maximum values define the coordinate system used by shapes drawn inside it.
+    pub fn new(position: PVec2, size: PVec2, x_min: f32, x_max: f32, y_min: f32, y_max: f32) -> ScaledViewBox {
+        ScaledViewBox { position, size, x_min, x_max, y_min, y_max, x_scale: Scale::Linear, y_scale: Scale::Linear }
+    }
+
+    /// Set how values are spread along each axis. With logarithmic scales, the minimum and
+    /// maximum values must be greater than zero.
+    pub fn scales(mut self, x_scale: Scale, y_scale: Scale) -> ScaledViewBox {
+        self.x_scale = x_scale;
+        self.y_scale = y_scale;
+        self
+    }
+
     fn clamp(n: f32, lower: f32, upper: f32) -> f32 {
         lower.max(n.min(upper))
     }
@@ -69,24 +121,104 @@ impl ScaledViewBox {
         Self::clamp_point(point, self.x_min, self.x_max, self.y_min, self.y_max)
     }
 
+    /// Scale a value to a decimal (0.0 - 1.0) within a range. If the range is empty, the value is
+    /// placed in the middle.
+    fn scale_range(n: f32, lower: f32, upper: f32, scale: Scale) -> f32 {
+        let (n, lower, upper) = (scale.apply(n), scale.apply(lower), scale.apply(upper));
+        if upper == lower { 0.5 } else { (n - lower) / (upper - lower) }
+    }
+
+    /// Undo `scale_range()`, giving the value at a decimal within a range.
+    fn unscale_range(dec: f32, lower: f32, upper: f32, scale: Scale) -> f32 {
+        let (lower, upper) = (scale.apply(lower), scale.apply(upper));
+        scale.invert(lower + dec * (upper - lower))
+    }
+
+    /// Scale a point to decimal values. (0.0, 0.0) corresponds to the top left of the view box,
+    /// so the Y axis is flipped to keep larger values at the top.
     fn scale_to_dec(&self, point: Vec2) -> Vec2 {
         Vec2::new(
-            (point.x - self.x_min) / self.x_max - self.x_min,
-            (point.y - self.y_min) / self.y_max - self.y_min,
+            Self::scale_range(point.x, self.x_min, self.x_max, self.x_scale),
+            1.0 - Self::scale_range(point.y, self.y_min, self.y_max, self.y_scale),
         )
     }
 
-    fn dec_to_pp(&self, point: Vec2) -> PVec2 {
-        PVec2::new(
-            (point.x * self.size.x as f32) as u16 + self.position.x,
-            (point.y * self.size.y as f32) as u16 + self.position.y,
+    fn dec_to_pp(&self, point: Vec2) -> Vec2 {
+        Vec2::new(
+            point.x * self.size.x.saturating_sub(1) as f32 + self.position.x as f32,
+            point.y * self.size.y.saturating_sub(1) as f32 + self.position.y as f32,
         )
     }
 
+    fn pp_to_dec(&self, point: PVec2) -> Vec2 {
+        let dec = |n: u16, position: u16, size: u16| match size {
+            0 | 1 => 0.5,
+            _ => (n.clamp(position, position + size - 1) - position) as f32 / (size - 1) as f32,
+        };
+        Vec2::new(dec(point.x, self.position.x, self.size.x), dec(point.y, self.position.y, self.size.y))
+    }
+
+    /// Translates floating-point values into plot area coordinates, without rounding them to a
+    /// cell. Whole numbers are at the center of a cell.
+    pub fn translate(&self, point: Vec2) -> Vec2 {
+        self.dec_to_pp(self.scale_to_dec(self.clamp_to_plot(point)))
+    }
+
     /// Translates floating-point values (defined by the bounds on the viewbox itself) into plot
     /// area coordinates.
     pub fn translate_to_plot(&self, point: Vec2) -> PVec2 {
-        self.dec_to_pp(self.scale_to_dec(self.clamp_to_plot(point)))
+        let translated: Vec2 = self.translate(point);
+        PVec2::new(translated.x.round() as u16, translated.y.round() as u16)
+    }
+
+    /// Translates plot area coordinates back into floating-point values, undoing
+    /// `translate_to_plot()` up to the rounding to a cell. Cells outside the view box are moved to
+    /// its edge.
+    pub fn translate_from_plot(&self, point: PVec2) -> Vec2 {
+        let dec: Vec2 = self.pp_to_dec(point);
+        Vec2::new(
+            Self::unscale_range(dec.x, self.x_min, self.x_max, self.x_scale),
+            Self::unscale_range(1.0 - dec.y, self.y_min, self.y_max, self.y_scale),
+        )
+    }
+
+    /// Check if a point is within the bounds of the view box. Points outside it would be moved
+    /// to its edge by `translate()`.
+    pub fn contains(&self, point: Vec2) -> bool {
+        let within = |n: f32, a: f32, b: f32| a.min(b) <= n && n <= a.max(b);
+        within(point.x, self.x_min, self.x_max) && within(point.y, self.y_min, self.y_max)
+    }
+
+    /// Cut a line down to the part of it within the bounds of the view box, or `None` if no part
+    /// of it is. Lines are cut on the scales of the axes, so that they stay straight once
+    /// translated.
+    pub fn clip_line(&self, start: Vec2, end: Vec2) -> Option<(Vec2, Vec2)> {
+        if self.contains(start) && self.contains(end) { return Some((start, end)) }
+        let apply = |p: Vec2| Vec2::new(self.x_scale.apply(p.x), self.y_scale.apply(p.y));
+        let (a, b) = (apply(start), apply(end));
+        let (low, high) = (apply(Vec2::new(self.x_min, self.y_min)), apply(Vec2::new(self.x_max, self.y_max)));
+        let d: Vec2 = a.to(&b);
+        // narrow down the part of the line (from 0 at the start to 1 at the end) that is inside
+        // each edge in turn (Liang-Barsky)
+        let (mut t0, mut t1) = (0.0f32, 1.0f32);
+        let edges: [(f32, f32); 4] = [
+            (-d.x, a.x - low.x.min(high.x)),
+            (d.x, low.x.max(high.x) - a.x),
+            (-d.y, a.y - low.y.min(high.y)),
+            (d.y, low.y.max(high.y) - a.y),
+        ];
+        for (p, q) in edges {
+            if p == 0.0 {
+                // parallel to the edge, and outside it
+                if q < 0.0 { return None }
+                continue
+            }
+            let t: f32 = q / p;
+            if p < 0.0 { t0 = t0.max(t) } else { t1 = t1.min(t) }
+            if t0 > t1 { return None }
+        }
+        let at = |t: f32| Vec2::new(self.x_scale.invert(a.x + d.x * t), self.y_scale.invert(a.y + d.y * t));
+        Some((at(t0), at(t1)))
     }
 }
 
@@ -100,17 +232,13 @@ impl Point {
     pub fn new(position: PVec2, symbol: char) -> Point {
         Point {position, symbol}
     }
-    /// Create a point based on a ScaledViewBox's coordinate system and convert it to integer coordinates.
-    pub fn in_svb(viewbox: ScaledViewBox, position: Vec2, symbol: char) -> Point {
-        Self::new(viewbox.translate_to_plot(position), symbol)
-    }
-    /// Draw the point in the selected plot area.
-    pub fn draw(&self, plot: &Plot) {
-        plot.put(self.symbol, &self.position);
+    /// Create a point in the cell nearest to a position in plot area coordinates.
+    pub fn at(position: Vec2, symbol: char) -> Point {
+        Self::new(PVec2::new(position.x.round() as u16, position.y.round() as u16), symbol)
     }
-    /// Draw the point in the selected ViewBox. This will translate to the ViewBox's origin.
-    pub fn draw_vb(&self, viewbox: &ViewBox) {
-        Self::new(self.position + viewbox.position, self.symbol).draw(&viewbox.plot)
+    /// Draw the point on a canvas.
+    pub fn draw(&self, canvas: &dyn Canvas) {
+        canvas.put(self.symbol, &self.position);
     }
 }
 
@@ -125,92 +253,230 @@ impl Line {
     pub fn new(start: PVec2, end: PVec2, symbol: char) -> Line {
         Line { start, end, symbol }
     }
-    pub fn in_svb(viewbox: ScaledViewBox, start: Vec2, end: Vec2, symbol: char) -> Line {
-        Line::new(viewbox.translate_to_plot(start), viewbox.translate_to_plot(end), symbol)
+    /// Create a line between the cells nearest to two positions in plot area coordinates.
+    pub fn between(start: Vec2, end: Vec2, symbol: char) -> Line {
+        let cell = |v: Vec2| PVec2::new(v.x.round() as u16, v.y.round() as u16);
+        Line::new(cell(start), cell(end), symbol)
     }
-    pub fn draw(&self, plot: &Plot) {
-        let dx: i16 = self.end.x as i16 - self.start.x as i16;
-        let dy: i16 = self.end.y as i16 - self.start.y as i16;
+    pub fn draw(&self, canvas: &dyn Canvas) {
+        let dx: i32 = self.end.x as i32 - self.start.x as i32;
+        let dy: i32 = self.end.y as i32 - self.start.y as i32;
         // if this is a straight line on either the X-axis or the Y-axis, make this easy
         if dy == 0 {
-            let line: String = self.symbol.to_string().repeat(dx.abs() as usize);
-            plot.put_str(line.as_str(), &PVec2::new(self.start.x.min(self.end.x), self.start.y))
+            let line: String = self.symbol.to_string().repeat(dx.unsigned_abs() as usize + 1);
+            canvas.put_str(line.as_str(), &PVec2::new(self.start.x.min(self.end.x), self.start.y))
         }
         else if dx == 0 {
-            let line: String = (self.symbol.to_string() + "\n").repeat(dy.abs() as usize);
-            plot.put_str(line.as_str(), &PVec2::new(self.start.x, self.start.y.min(self.end.y)))
+            let line: String = (self.symbol.to_string() + "\n").repeat(dy.unsigned_abs() as usize + 1);
+            canvas.put_str(line.as_str(), &PVec2::new(self.start.x, self.start.y.min(self.end.y)))
         }
-        // make the string the hard way; if you somehow make it to this with either dx or dy = 0, I
-        // would be very concerned and would expect this to fail spectacularly. Good luck, friend.
+        // otherwise, walk the line one cell at a time (Bresenham's algorithm)
         else {
-            // determine our step size on the x axis
-            // we scale our step value so that the y step is 1; this allows us to generate our line,
-            // line by line
-            let mut step_x: f32 = dx as f32 / dy as f32;
-            // create position and target values
-            let mut px: f32 = self.start.x as f32;
-            let mut tx: f32 = self.end.x as f32;
-            if (step_x < 0.0) {
-                px = self.end.x as f32;
-                tx = self.start.x as f32;
-            }
-            let mut py: u16 = self.start.y.min(self.end.y);
-            let ty: u16 = self.end.y.max(self.start.y);
-            let mut lines: String = "".to_string();
-            // create the string for the line
-            // this is probably horribly inefficient, I should really figure out a way to make this
-            // run better. it works for now at least.
-            while (px != tx && py != ty) {
-                let prev_x: f32 = px;
-                px += step_x;
-                // get start and end points for the actual line segment
-                let str_start: i16 = px.min(prev_x).floor() as i16;
-                let str_end: i16 = px.max(prev_x).ceil() as i16;
-                // get the length of the line segment
-                let str_len: i16 = str_end - str_start;
-                // fill from 0 to start with whitespace, start to end with character
-                let line: String = ' '.to_string().repeat(str_start as usize) + self.symbol.to_string().repeat(str_len as usize).as_str();
-                // finish it off with a newline
-                lines += (line + "\n").as_str();
-                py += 1
+            let step_x: i32 = dx.signum();
+            let step_y: i32 = dy.signum();
+            let mut px: i32 = self.start.x as i32;
+            let mut py: i32 = self.start.y as i32;
+            let mut err: i32 = dx.abs() - dy.abs();
+            loop {
+                canvas.put(self.symbol, &PVec2::new(px as u16, py as u16));
+                if px == self.end.x as i32 && py == self.end.y as i32 { break }
+                let e2: i32 = err * 2;
+                if e2 > -dy.abs() {
+                    err -= dy.abs();
+                    px += step_x;
+                }
+                if e2 < dx.abs() {
+                    err += dx.abs();
+                    py += step_y;
+                }
             }
-            // push this god-awful monstrosity to the plot
-            plot.put_str_transparent(lines.as_str(), &PVec2::new(self.start.x, self.start.y.min(self.end.y)));
         }
     }
-    pub fn draw_vb(&self, viewbox: &ViewBox) {
-        Self::new(self.start + viewbox.position, self.end + viewbox.position, self.symbol).draw(&viewbox.plot)
-    }
 }
 
-/// A rectangle. Can be drawn on a plot area.
+/// A rectangle. Can be drawn on a plot area, either as an outline or filled in.
 pub struct Rect {
     position: PVec2,
     size: PVec2,
     symbol: char,
+    filled: bool,
+    /// Opposite corners in plot area coordinates, before being snapped to cells. Used for
+    /// drawing with dots.
+    exact: Option<(Vec2, Vec2)>,
 }
 
 impl Rect {
     pub fn new(position: PVec2, size: PVec2, symbol: char) -> Rect {
-        Rect { position, size, symbol }
+        Rect { position, size, symbol, filled: false, exact: None }
+    }
+
+    /// Get the first and last cells with centers inside a range. If no cell centers are inside
+    /// it, the nearest cell is used.
+    fn cell_range(a: f32, b: f32) -> (u16, u16) {
+        let start: f32 = (a.min(b) - 1e-3).ceil();
+        let end: f32 = (a.max(b) + 1e-3).floor();
+        if end < start {
+            let middle: f32 = ((a + b) / 2.0).round();
+            (middle as u16, middle as u16)
+        } else {
+            (start as u16, end as u16)
+        }
+    }
+
+    /// Create a rectangle covering every cell with its center inside the rectangle between two
+    /// opposite corners, in plot area coordinates.
+    pub fn covering(a: Vec2, b: Vec2, symbol: char) -> Rect {
+        let (x_start, x_end) = Self::cell_range(a.x, b.x);
+        let (y_start, y_end) = Self::cell_range(a.y, b.y);
+        Rect {
+            exact: Some((a, b)),
+            ..Rect::new(PVec2::new(x_start, y_start), PVec2::new(x_end - x_start, y_end - y_start), symbol)
+        }
     }
 
-    pub fn in_svb(viewbox: ScaledViewBox, position: Vec2, size: Vec2, symbol: char) -> Rect {
-        Rect::new(viewbox.translate_to_plot(position), viewbox.translate_to_plot(size), symbol)
+    /// Fill in the rectangle when it is drawn, instead of only drawing the outline.
+    pub fn fill(mut self) -> Rect {
+        self.filled = true;
+        self
     }
 
-    pub fn draw(&self, plot: &Plot) {
+    pub fn draw(&self, canvas: &dyn Canvas) {
+        if self.filled {
+            let row: String = self.symbol.to_string().repeat(self.size.x as usize + 1);
+            let rows: String = vec![row; self.size.y as usize + 1].join("\n");
+            canvas.put_str(rows.as_str(), &self.position);
+            return
+        }
         let tl: PVec2 = self.position;
         let tr: PVec2 = PVec2::new(self.position.x + self.size.x, self.position.y);
         let bl: PVec2 = PVec2::new(self.position.x, self.position.y + self.size.y);
         let br: PVec2 = PVec2::new(self.position.x + self.size.x, self.position.y + self.size.y);
 
-        Line::new(tl, tr, self.symbol).draw(plot);
-        Line::new(bl, br, self.symbol).draw(plot);
-        Line::new(tl, bl, self.symbol).draw(plot);
-        Line::new(tr, br, self.symbol).draw(plot);
+        Line::new(tl, tr, self.symbol).draw(canvas);
+        Line::new(bl, br, self.symbol).draw(canvas);
+        Line::new(tl, bl, self.symbol).draw(canvas);
+        Line::new(tr, br, self.symbol).draw(canvas);
+    }
+
+    /// Draw the rectangle into a grid of dots. Rectangles created from exact corners keep their
+    /// exact size, instead of being snapped to whole cells.
+    pub fn draw_dots(&self, dots: &Dots) {
+        let (a, b) = self.exact.unwrap_or((
+            Vec2::new(self.position.x as f32 - 0.5, self.position.y as f32 - 0.5),
+            Vec2::new((self.position.x + self.size.x) as f32 + 0.5, (self.position.y + self.size.y) as f32 + 0.5),
+        ));
+        if self.filled {
+            dots.rect(a, b);
+            return
+        }
+        let (tr, bl) = (Vec2::new(b.x, a.y), Vec2::new(a.x, b.y));
+        dots.line(a, tr);
+        dots.line(tr, b);
+        dots.line(b, bl);
+        dots.line(bl, a);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::canvas::TextCanvas;
+    use crate::snapshot::assert_snapshot;
+
+    #[test]
+    fn line_draw() {
+        let canvas: TextCanvas = TextCanvas::new(20, 10);
+        Line::new(PVec2::new(1, 1), PVec2::new(18, 1), '-').draw(&canvas);
+        Line::new(PVec2::new(1, 2), PVec2::new(1, 8), '|').draw(&canvas);
+        Line::new(PVec2::new(3, 3), PVec2::new(17, 8), '*').draw(&canvas);
+        Line::new(PVec2::new(17, 3), PVec2::new(14, 8), '#').draw(&canvas);
+        Line::new(PVec2::new(8, 5), PVec2::new(8, 5), 'o').draw(&canvas);
+        assert_snapshot("line_draw", &canvas);
     }
-    pub fn draw_vb(&self, viewbox: &ViewBox) {
-        Self::new(self.position + viewbox.position, self.size, self.symbol).draw(&viewbox.plot)
+
+    #[test]
+    fn line_draw_is_symmetric() {
+        let forward: TextCanvas = TextCanvas::new(12, 6);
+        let backward: TextCanvas = TextCanvas::new(12, 6);
+        Line::new(PVec2::new(0, 0), PVec2::new(11, 5), '*').draw(&forward);
+        Line::new(PVec2::new(11, 5), PVec2::new(0, 0), '*').draw(&backward);
+        assert_eq!(forward.lines().iter().filter(|l| l.contains('*')).count(), 6);
+        assert_eq!(forward.get(&PVec2::new(0, 0)), '*');
+        assert_eq!(backward.get(&PVec2::new(11, 5)), '*');
+    }
+
+    #[test]
+    fn rect_draw() {
+        let canvas: TextCanvas = TextCanvas::new(20, 8);
+        Rect::new(PVec2::new(1, 1), PVec2::new(7, 5), '+').draw(&canvas);
+        Rect::new(PVec2::new(11, 2), PVec2::new(6, 3), '#').fill().draw(&canvas);
+        Rect::new(PVec2::new(4, 3), PVec2::new(0, 0), 'x').fill().draw(&canvas);
+        assert_snapshot("rect_draw", &canvas);
+    }
+
+    #[test]
+    fn rect_covering_covers_cell_centres() {
+        let viewbox: ScaledViewBox = ScaledViewBox::new(PVec2::new(0, 0), PVec2::new(11, 11), 0.0, 10.0, 0.0, 10.0);
+        let canvas: TextCanvas = TextCanvas::new(11, 11);
+        Rect::covering(viewbox.translate(Vec2::new(2.0, 0.0)), viewbox.translate(Vec2::new(5.0, 4.0)), '#').fill().draw(&canvas);
+        let filled: Vec<PVec2> = (0..11).flat_map(|y| (0..11).map(move |x| PVec2::new(x, y)))
+            .filter(|p| canvas.get(p) == '#')
+            .collect();
+        assert_eq!(filled.len(), 4 * 5);
+        assert!(filled.iter().all(|p| (2..=5).contains(&p.x) && (6..=10).contains(&p.y)));
+    }
+
+    #[test]
+    fn translate_to_plot_corners() {
+        let viewbox: ScaledViewBox = ScaledViewBox::new(PVec2::new(5, 2), PVec2::new(21, 11), -1.0, 1.0, 0.0, 100.0);
+        // the Y axis is flipped, so the minimum is at the bottom
+        assert_eq!(viewbox.translate_to_plot(Vec2::new(-1.0, 0.0)), PVec2::new(5, 12));
+        assert_eq!(viewbox.translate_to_plot(Vec2::new(1.0, 100.0)), PVec2::new(25, 2));
+        assert_eq!(viewbox.translate_to_plot(Vec2::new(0.0, 50.0)), PVec2::new(15, 7));
+        assert_eq!(viewbox.translate(Vec2::new(0.25, 25.0)), Vec2::new(17.5, 9.5));
+    }
+
+    #[test]
+    fn translate_to_plot_clamps() {
+        let viewbox: ScaledViewBox = ScaledViewBox::new(PVec2::new(5, 2), PVec2::new(20, 10), 0.0, 1.0, 0.0, 1.0);
+        // the last cell is at the position plus the size, minus one
+        assert_eq!(viewbox.translate_to_plot(Vec2::new(-5.0, 5.0)), PVec2::new(5, 2));
+        assert_eq!(viewbox.translate_to_plot(Vec2::new(5.0, -5.0)), PVec2::new(24, 11));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn translate_to_plot_empty_range() {
+        let viewbox: ScaledViewBox = ScaledViewBox::new(PVec2::new(0, 0), PVec2::new(10, 10), 3.0, 3.0, 3.0, 3.0);
+        assert_eq!(viewbox.translate_to_plot(Vec2::new(3.0, 3.0)), PVec2::new(5, 5));
+    }
+
+    #[test]
+    fn lines_are_clipped_to_the_bounds() {
+        let viewbox: ScaledViewBox = ScaledViewBox::new(PVec2::new(0, 0), PVec2::new(10, 10), 0.0, 10.0, 0.0, 10.0);
+        assert_eq!(viewbox.clip_line(Vec2::new(1.0, 1.0), Vec2::new(2.0, 3.0)), Some((Vec2::new(1.0, 1.0), Vec2::new(2.0, 3.0))));
+        assert_eq!(viewbox.clip_line(Vec2::new(-5.0, 5.0), Vec2::new(15.0, 5.0)), Some((Vec2::new(0.0, 5.0), Vec2::new(10.0, 5.0))));
+        assert_eq!(viewbox.clip_line(Vec2::new(5.0, 5.0), Vec2::new(5.0, 20.0)), Some((Vec2::new(5.0, 5.0), Vec2::new(5.0, 10.0))));
+        assert_eq!(viewbox.clip_line(Vec2::new(-5.0, 0.0), Vec2::new(0.0, -5.0)), None);
+        assert!(!viewbox.contains(Vec2::new(10.5, 0.0)));
+    }
+
+    #[test]
+    fn translate_to_plot_log_scale() {
+        let viewbox: ScaledViewBox = ScaledViewBox::new(PVec2::new(0, 0), PVec2::new(31, 31), 1.0, 1000.0, 1.0, 1000.0)
+            .scales(Scale::Log10, Scale::Linear);
+        assert_eq!(viewbox.translate_to_plot(Vec2::new(10.0, 1.0)).x, 10);
+        assert_eq!(viewbox.translate_to_plot(Vec2::new(100.0, 1.0)).x, 20);
+        assert_eq!(Scale::Log2.invert(Scale::Log2.apply(8.0)), 8.0);
+    }
+
+    #[test]
+    fn translate_from_plot() {
+        let viewbox: ScaledViewBox = ScaledViewBox::new(PVec2::new(5, 2), PVec2::new(31, 11), 1.0, 1000.0, 0.0, 10.0)
+            .scales(Scale::Log10, Scale::Linear);
+        let point: Vec2 = viewbox.translate_from_plot(PVec2::new(15, 7));
+        assert!((point.x - 10.0).abs() < 1e-3 && (point.y - 5.0).abs() < 1e-5);
+        assert_eq!(viewbox.translate_to_plot(point), PVec2::new(15, 7));
+        // cells outside the view box are moved to its edge
+        assert_eq!(viewbox.translate_from_plot(PVec2::new(0, 50)).y, 0.0);
+    }
+}
@@ -0,0 +1,186 @@
+// clplot::renderer::svg - scalable vector graphics output
+//     Copyright (C) 2024  Dustin Thomas <stdio@cptlobster.dev>
+//
+//     This program is free software: you can redistribute it and/or modify
+//     it under the terms of the GNU General Public License as published by
+//     the Free Software Foundation, either version 3 of the License, or
+//     (at your option) any later version.
+//
+//     This program is distributed in the hope that it will be useful,
+//     but WITHOUT ANY WARRANTY; without even the implied warranty of
+//     MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//     GNU General Public License for more details.
+//
+//     You should have received a copy of the GNU General Public License
+//     along with this program.  If not, see <https://www.gnu.org/licenses/>.
+/// SVG output. Scenes are drawn as vector shapes and text, using the same layout as on the
+/// terminal: each cell becomes a fixed-size box of pixels.
+use std::cell::{RefCell, RefMut};
+use std::fmt::Write as _;
+use std::io::Write;
+use crate::data::Vec2;
+use crate::renderer::canvas::{Canvas, TextCanvas};
+use crate::renderer::color::{rgb, Color, Style};
+use crate::renderer::scene::{Element, Scene, Shape, CELL_HEIGHT, CELL_WIDTH};
+
+/// Size of a cell, in pixels.
+const CELL: (f32, f32) = (CELL_WIDTH as f32, CELL_HEIGHT as f32);
+
+/// Size of text, in pixels. Monospace fonts at this size are about one cell wide per character.
+const FONT_SIZE: f32 = 20.0;
+
+/// Distance from the top of a cell to the baseline of its text, in pixels.
+const BASELINE: f32 = 18.0;
+
+/// Radius of point markers, in pixels.
+const MARKER_RADIUS: f32 = 4.0;
+
+/// Width of lines, in pixels.
+const STROKE_WIDTH: f32 = 2.0;
+
+/// Get the position of a point in plot area coordinates, in pixels.
+fn pixels(point: Vec2) -> (f32, f32) {
+    ((point.x + 0.5) * CELL.0, (point.y + 0.5) * CELL.1)
+}
+
+/// Get the SVG color for a style.
+fn color(style: &Style) -> String {
+    let (r, g, b) = rgb(style.foreground.unwrap_or(Color::Black));
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Escape text for use in an SVG document.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Write the SVG for a point marker. The shape depends on the symbol the point is drawn with in
+/// character mode.
+fn marker(out: &mut String, symbol: char, (x, y): (f32, f32)) {
+    let r: f32 = MARKER_RADIUS;
+    let _ = match symbol {
+        '+' => writeln!(out, r#"<path d="M{} {}H{}M{} {}V{}" fill="none"/>"#, x - r, y, x + r, x, y - r, y + r),
+        'x' => writeln!(out, r#"<path d="M{} {}L{} {}M{} {}L{} {}" fill="none"/>"#, x - r, y - r, x + r, y + r, x - r, y + r, x + r, y - r),
+        'o' => writeln!(out, r#"<circle cx="{}" cy="{}" r="{}" fill="none"/>"#, x, y, r),
+        '#' => writeln!(out, r#"<rect x="{}" y="{}" width="{}" height="{}"/>"#, x - r, y - r, r * 2.0, r * 2.0),
+        _ => writeln!(out, r#"<circle cx="{}" cy="{}" r="{}"/>"#, x, y, r),
+    };
+}
+
+/// Draw a scene as an SVG document.
+pub fn render(scene: &Scene) -> String {
+    let (width, height) = (scene.width as f32 * CELL.0, scene.height as f32 * CELL.1);
+    let mut out: String = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}" font-family="monospace" font-size="{2}">"#,
+        width, height, FONT_SIZE,
+    );
+    let _ = writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#);
+    for element in scene.elements() {
+        match element {
+            Element::Text { text, position, style } => {
+                let weight: &str = if style.bold { r#" font-weight="bold""# } else { "" };
+                for (i, line) in text.split('\n').enumerate() {
+                    if line.trim().is_empty() { continue }
+                    let (x, y) = (position.x as f32 * CELL.0, (position.y as usize + i) as f32 * CELL.1 + BASELINE);
+                    let _ = writeln!(
+                        out,
+                        r#"<text x="{}" y="{}" fill="{}"{} xml:space="preserve">{}</text>"#,
+                        x, y, color(style), weight, escape(line),
+                    );
+                }
+            }
+            Element::Shapes { shapes, symbol, style, .. } => {
+                let _ = writeln!(out, r#"<g stroke="{0}" fill="{0}" stroke-width="{1}">"#, color(style), STROKE_WIDTH);
+                for shape in shapes {
+                    match *shape {
+                        Shape::Line(start, end) => {
+                            let ((x1, y1), (x2, y2)) = (pixels(start), pixels(end));
+                            let _ = writeln!(out, r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#, x1, y1, x2, y2);
+                        }
+                        Shape::Point(position) => marker(&mut out, *symbol, pixels(position)),
+                        Shape::Rect(a, b) => {
+                            let ((x1, y1), (x2, y2)) = (pixels(a), pixels(b));
+                            let _ = writeln!(
+                                out,
+                                r#"<rect x="{}" y="{}" width="{}" height="{}" stroke="none"/>"#,
+                                x1.min(x2), y1.min(y2), (x2 - x1).abs(), (y2 - y1).abs(),
+                            );
+                        }
+                    }
+                }
+                let _ = writeln!(out, "</g>");
+            }
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Canvas that writes the scenes drawn on it to a sink as an SVG document, when finished.
+/// Characters drawn directly on the canvas, rather than as part of a scene, are left out.
+pub struct SvgCanvas<W: Write> {
+    frame: TextCanvas,
+    scene: RefCell<Scene>,
+    sink: RefCell<W>,
+}
+
+impl<W: Write> SvgCanvas<W> {
+    /// Create a new canvas of a specified width/height in cells, that writes to a sink.
+    pub fn new(sink: W, width: u16, height: u16) -> SvgCanvas<W> {
+        SvgCanvas { frame: TextCanvas::new(width, height), scene: RefCell::new(Scene::new(width, height)), sink: RefCell::new(sink) }
+    }
+}
+
+impl<W: Write> Canvas for SvgCanvas<W> {
+    fn frame(&self) -> &TextCanvas {
+        &self.frame
+    }
+
+    fn frame_mut(&mut self) -> &mut TextCanvas {
+        &mut self.frame
+    }
+
+    /// The scene is kept as it is, and drawn as vectors when the canvas is finished.
+    fn draw_scene(&self, scene: &Scene) {
+        self.scene.borrow_mut().extend(scene);
+    }
+
+    fn clear(&self) {
+        self.frame.clear();
+        let (width, height) = (self.frame.width, self.frame.height);
+        *self.scene.borrow_mut() = Scene::new(width, height);
+    }
+
+    fn set_size(&mut self, width: u16, height: u16) {
+        self.frame.set_size(width, height);
+        *self.scene.borrow_mut() = Scene::new(width, height);
+    }
+
+    fn finish(&self) {
+        let mut sink: RefMut<W> = self.sink.borrow_mut();
+        sink.write_all(render(&self.scene.borrow()).as_bytes()).and_then(|_| sink.flush()).expect("Error writing output");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::PVec2;
+    use crate::renderer::dots::DotMode;
+
+    #[test]
+    fn scenes_are_drawn_as_vectors() {
+        let mut scene: Scene = Scene::new(10, 5);
+        scene.text("a<b", PVec2::new(1, 0), Style::PLAIN.bold());
+        scene.shapes(vec![Shape::Line(Vec2::new(0.0, 4.0), Vec2::new(9.0, 4.0))], '-', DotMode::Ascii, Style::PLAIN);
+        scene.shapes(vec![Shape::Rect(Vec2::new(1.5, 1.0), Vec2::new(2.5, 3.5))], '#', DotMode::HalfBlock, Style::color(Color::Red));
+        let svg: String = render(&scene);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="120" height="120""#));
+        assert!(svg.contains(r##"<text x="12" y="18" fill="#000000" font-weight="bold" xml:space="preserve">a&lt;b</text>"##));
+        assert!(svg.contains(r##"<line x1="6" y1="108" x2="114" y2="108"/>"##));
+        assert!(svg.contains(r##"<g stroke="#ff0000" fill="#ff0000" stroke-width="2">"##));
+        assert!(svg.contains(r##"<rect x="24" y="36" width="12" height="60" stroke="none"/>"##));
+    }
+}
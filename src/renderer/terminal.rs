@@ -15,15 +15,116 @@
 /// Direct interface for getting information about the terminal. Primarily used for getting the
 /// screen size, and for learning about the capabilities of this terminal. (what colors does it
 /// support? charsets?)
+use std::env;
+use std::io::{self, stdout, IsTerminal};
+use std::time::Duration;
+use crossterm::event::{poll, read, Event};
 use crossterm::terminal::size;
+use crate::renderer::color::{ColorChoice, ColorDepth};
+use crate::renderer::dots::DotMode;
+
+/// Size to use when there is no terminal to measure, and `COLUMNS`/`LINES` aren't set.
+const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+/// Get the size of the terminal, in cells. If there is no terminal (for example, in a CI job),
+/// the `COLUMNS` and `LINES` environment variables are used, or else 80x24.
 pub fn get_size() -> (u16, u16) {
-    size().expect("Error with terminal interaction")
+    size().ok().filter(|(width, height)| *width > 0 && *height > 0).unwrap_or_else(|| {
+        let from_env = |name: &str, default: u16| env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default);
+        (from_env("COLUMNS", DEFAULT_SIZE.0), from_env("LINES", DEFAULT_SIZE.1))
+    })
+}
+
+/// Wait up to `timeout` for the terminal to be resized. Returns the new size, in cells, if it was
+/// resized; if it was resized several times, only the last size counts. Other events (such as key
+/// presses) are discarded.
+pub fn poll_resize(timeout: Duration) -> io::Result<Option<(u16, u16)>> {
+    let mut resized: Option<(u16, u16)> = None;
+    let mut wait: Duration = timeout;
+    while poll(wait)? {
+        if let Event::Resize(width, height) = read()? { resized = Some((width, height)); }
+        wait = Duration::ZERO;
+    }
+    Ok(resized)
+}
+
+/// Check if the terminal can show Unicode characters, going by the locale settings (`LC_ALL`,
+/// `LC_CTYPE` and `LANG`, in order of priority).
+pub fn supports_unicode() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| {
+            let value: String = value.to_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+        .unwrap_or(false)
+}
+
+/// Detect the colors the terminal can show, from `COLORTERM` (for 24-bit color) and `TERM`.
+pub fn color_depth() -> ColorDepth {
+    let colorterm: String = env::var("COLORTERM").unwrap_or_default().to_lowercase();
+    if colorterm.contains("truecolor") || colorterm.contains("24bit") { return ColorDepth::TrueColor }
+    match env::var("TERM").unwrap_or_default().as_str() {
+        "" | "dumb" => ColorDepth::None,
+        term if term.contains("256color") => ColorDepth::Ansi256,
+        _ => ColorDepth::Ansi16,
+    }
 }
 
-pub fn get_width() -> u16 {
-    get_size().0
+/// What the terminal that stdout goes to can do.
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Capabilities {
+    /// Whether stdout is a terminal, rather than a file or pipe.
+    pub tty: bool,
+    /// Whether the terminal can show Unicode characters.
+    pub unicode: bool,
+    /// Colors the terminal can show.
+    pub colors: ColorDepth,
 }
 
-pub fn get_height() -> u16 {
-    get_size().1
-}
\ No newline at end of file
+impl Capabilities {
+    /// Capabilities of a plain text file: ASCII only, without color.
+    pub const PLAIN: Capabilities = Capabilities { tty: false, unicode: false, colors: ColorDepth::None };
+
+    /// Detect the capabilities of the terminal. If stdout isn't a terminal, only its Unicode
+    /// support is detected, since files and pipes can still hold Unicode text.
+    pub fn detect() -> Capabilities {
+        let tty: bool = stdout().is_terminal();
+        Capabilities {
+            tty,
+            unicode: supports_unicode(),
+            colors: if tty { color_depth() } else { ColorDepth::None },
+        }
+    }
+
+    /// Decide which colors to draw with. `Auto` uses the detected colors unless `NO_COLOR` is
+    /// set; `Always` uses at least the 16 standard colors, even when not writing to a terminal.
+    pub fn choose_colors(&self, choice: ColorChoice) -> ColorDepth {
+        match choice {
+            ColorChoice::Never => ColorDepth::None,
+            ColorChoice::Auto if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) => ColorDepth::None,
+            ColorChoice::Auto => self.colors,
+            ColorChoice::Always => color_depth().max(ColorDepth::Ansi16),
+        }
+    }
+
+    /// Get the best way to draw shapes, given the mode that suits a chart best. Unicode modes are
+    /// only used on terminals that support them, so that piped output and logs stay readable.
+    pub fn dot_mode(&self, best: DotMode) -> DotMode {
+        if self.tty && self.unicode { best } else { DotMode::Ascii }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_output_degrades_to_ascii() {
+        assert_eq!(Capabilities::PLAIN.dot_mode(DotMode::Braille), DotMode::Ascii);
+        assert_eq!(Capabilities::PLAIN.choose_colors(ColorChoice::Auto), ColorDepth::None);
+        let terminal: Capabilities = Capabilities { tty: true, unicode: true, ..Capabilities::PLAIN };
+        assert_eq!(terminal.dot_mode(DotMode::Braille), DotMode::Braille);
+    }
+}
@@ -0,0 +1,76 @@
+/// Snapshot testing. Drawings are rendered headlessly onto a `TextCanvas`, and compared cell for
+/// cell against golden text files in `tests/snapshots`. Set `UPDATE_SNAPSHOTS=1` to write the
+/// current output as the new golden files instead.
+use std::env;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use crate::chart::base::Chart;
+use crate::data::{find_format, Table};
+use crate::renderer::canvas::{Canvas, TextCanvas};
+
+/// Get the path of a file in the `tests` directory.
+fn test_path(parts: &[&str]) -> PathBuf {
+    let mut path: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
+    path.extend(parts);
+    path
+}
+
+/// Read a fixture from `tests/fixtures` as a table.
+pub fn fixture(name: &str, format: &str) -> Table {
+    let file: File = File::open(test_path(&["fixtures", name])).expect("Could not open fixture");
+    Table::read(file, find_format(format).expect("Unknown format")).expect("Could not read fixture")
+}
+
+/// Create a canvas of a specified width/height for a chart to draw on.
+pub fn canvas(width: u16, height: u16) -> Box<dyn Canvas> {
+    Box::new(TextCanvas::new(width, height))
+}
+
+/// Get the contents of a canvas as they are stored in a golden file; Trailing spaces are left off
+/// each line.
+fn render(canvas: &dyn Canvas) -> String {
+    canvas.lines().iter().map(|line| line.trim_end().to_string() + "\n").collect()
+}
+
+/// Find the first cell (row and column) where two renderings differ.
+fn first_difference(expected: &str, actual: &str) -> (usize, usize) {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    for row in 0..expected.len().max(actual.len()) {
+        let a: Vec<char> = expected.get(row).unwrap_or(&"").chars().collect();
+        let b: Vec<char> = actual.get(row).unwrap_or(&"").chars().collect();
+        if let Some(column) = (0..a.len().max(b.len())).find(|i| a.get(*i).unwrap_or(&' ') != b.get(*i).unwrap_or(&' ')) {
+            return (row, column)
+        }
+    }
+    (0, 0)
+}
+
+/// Check that a canvas matches the golden file `tests/snapshots/<name>.txt`.
+pub fn assert_snapshot(name: &str, canvas: &dyn Canvas) {
+    let path: PathBuf = test_path(&["snapshots", &format!("{}.txt", name)]);
+    let actual: String = render(canvas);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().expect("Snapshot path has no parent")).expect("Could not create snapshot directory");
+        fs::write(&path, &actual).expect("Could not write snapshot");
+        return
+    }
+    let expected: String = fs::read_to_string(&path).unwrap_or_else(|_| panic!(
+        "missing snapshot {}; run with UPDATE_SNAPSHOTS=1 to create it\n{}", path.display(), actual,
+    ));
+    if expected != actual {
+        let (row, column) = first_difference(&expected, &actual);
+        panic!(
+            "snapshot {} differs at row {}, column {}\n--- expected\n{}--- actual\n{}",
+            name, row, column, expected, actual,
+        );
+    }
+}
+
+/// Draw a chart, and check that it matches the golden file `tests/snapshots/<name>.txt`. Returns
+/// the lines of the canvas, for checking details of the drawing directly.
+pub fn assert_chart(name: &str, chart: &impl Chart) -> Vec<String> {
+    chart.draw();
+    assert_snapshot(name, chart.base().canvas());
+    chart.base().canvas().lines()
+}
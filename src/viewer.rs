@@ -0,0 +1,250 @@
+/// Interactive viewer. A chart is shown on the whole terminal, and the range of values it shows
+/// can be moved around with the keyboard: arrow keys pan, + and - zoom, r resets the view and q
+/// quits. A crosshair, moved with the mouse or with shift and the arrow keys, picks out the point
+/// nearest to it, and its exact values are shown in the status line.
+use std::io;
+use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crate::chart::base::{format_number, Axis, Chart};
+use crate::data::{PVec2, Series, Vec2};
+use crate::renderer::canvas::Canvas;
+use crate::renderer::color::{ColorDepth, Style, AXIS_COLOR};
+use crate::renderer::dots::DotMode;
+use crate::renderer::scene::{Scene, Shape};
+use crate::renderer::screen::Screen;
+use crate::renderer::shapes::ScaledViewBox;
+
+/// How far each arrow key press pans, as a fraction of the range shown.
+const PAN_STEP: f32 = 0.1;
+/// How much each + key press shrinks the range shown; - grows it by the inverse.
+const ZOOM_STEP: f32 = 0.8;
+/// Keys to show in the status line.
+const HELP: &str = "arrows: pan  +/-: zoom  shift+arrows: crosshair  c: hide  r: reset  q: quit";
+
+/// Something to do in response to a key press or mouse event.
+#[derive(PartialEq, Copy, Clone, Debug)]
+enum Action {
+    /// Move the view (see `BaseChart::move_view`).
+    Move(Vec2, f32),
+    /// Go back to the view the chart started with.
+    Reset,
+    /// Move the crosshair by a number of cells, showing it if it is hidden.
+    MoveCrosshair(i32, i32),
+    /// Put the crosshair on a cell.
+    PlaceCrosshair(PVec2),
+    /// Show or hide the crosshair.
+    ToggleCrosshair,
+    Quit,
+}
+
+/// Get the action for a key press, if it has one.
+fn action(key: KeyEvent) -> Option<Action> {
+    let shift: bool = key.modifiers.contains(KeyModifiers::SHIFT);
+    match key.code {
+        KeyCode::Left if shift => Some(Action::MoveCrosshair(-1, 0)),
+        KeyCode::Right if shift => Some(Action::MoveCrosshair(1, 0)),
+        KeyCode::Up if shift => Some(Action::MoveCrosshair(0, -1)),
+        KeyCode::Down if shift => Some(Action::MoveCrosshair(0, 1)),
+        KeyCode::Left => Some(Action::Move(Vec2::new(-PAN_STEP, 0.0), 1.0)),
+        KeyCode::Right => Some(Action::Move(Vec2::new(PAN_STEP, 0.0), 1.0)),
+        KeyCode::Up => Some(Action::Move(Vec2::new(0.0, PAN_STEP), 1.0)),
+        KeyCode::Down => Some(Action::Move(Vec2::new(0.0, -PAN_STEP), 1.0)),
+        KeyCode::Char('+') | KeyCode::Char('=') => Some(Action::Move(Vec2::new(0.0, 0.0), ZOOM_STEP)),
+        KeyCode::Char('-') | KeyCode::Char('_') => Some(Action::Move(Vec2::new(0.0, 0.0), 1.0 / ZOOM_STEP)),
+        KeyCode::Char('r') => Some(Action::Reset),
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Quit),
+        KeyCode::Char('c') => Some(Action::ToggleCrosshair),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+        _ => None,
+    }
+}
+
+/// Get the action for a mouse event, if it has one. Moving or clicking puts the crosshair under
+/// the mouse, and scrolling zooms.
+fn mouse_action(event: MouseEvent) -> Option<Action> {
+    match event.kind {
+        MouseEventKind::Moved | MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left) => {
+            Some(Action::PlaceCrosshair(PVec2::new(event.column, event.row)))
+        }
+        MouseEventKind::ScrollUp => Some(Action::Move(Vec2::new(0.0, 0.0), ZOOM_STEP)),
+        MouseEventKind::ScrollDown => Some(Action::Move(Vec2::new(0.0, 0.0), 1.0 / ZOOM_STEP)),
+        _ => None,
+    }
+}
+
+/// A point picked out by the crosshair.
+struct Pick<'a> {
+    series: &'a Series,
+    point: Vec2,
+}
+
+/// State of the viewer: the chart, the view it started with, and where the crosshair is.
+struct Viewer<'a> {
+    chart: &'a mut dyn Chart,
+    home: (Axis, Axis),
+    /// Position of the crosshair, in the values of the axes, so that it stays over the same
+    /// values when the view is moved. `None` while the crosshair is hidden.
+    crosshair: Option<Vec2>,
+}
+
+impl<'a> Viewer<'a> {
+    fn new(chart: &'a mut dyn Chart) -> Viewer<'a> {
+        let home: (Axis, Axis) = (chart.base().x().clone(), chart.base().y().clone());
+        Viewer { chart, home, crosshair: None }
+    }
+
+    /// Get the point nearest to the crosshair, out of the points in view. Distance is measured in
+    /// cells, with rows counting double, since cells are about twice as tall as they are wide.
+    fn pick(&self, viewbox: &ScaledViewBox) -> Option<Pick<'_>> {
+        let target: Vec2 = viewbox.translate(self.crosshair?);
+        let distance = |point: &Vec2| {
+            let d: Vec2 = target.to(&viewbox.translate(*point));
+            d.x * d.x + 4.0 * d.y * d.y
+        };
+        self.chart.series().iter()
+            .flat_map(|series| series.points.iter().map(move |point| (series, point)))
+            .filter(|(_, point)| self.chart.base().can_show(point) && viewbox.contains(**point))
+            .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+            .map(|(series, point)| Pick { series, point: *point })
+    }
+
+    /// Get the cell at the center of the data area.
+    fn center(&self) -> PVec2 {
+        let (position, size) = self.chart.base().data_area();
+        PVec2::new(position.x + size.x / 2, position.y + size.y / 2)
+    }
+
+    /// Do an action. Returns false once the viewer should be closed.
+    fn apply(&mut self, action: Action) -> bool {
+        let viewbox: ScaledViewBox = self.chart.base().viewbox();
+        match action {
+            Action::Move(shift, zoom) => self.chart.base_mut().move_view(shift, zoom),
+            Action::Reset => {
+                self.chart.base_mut().set_x(self.home.0.clone());
+                self.chart.base_mut().set_y(self.home.1.clone());
+            }
+            Action::MoveCrosshair(dx, dy) => {
+                let start: PVec2 = match self.crosshair {
+                    Some(crosshair) => viewbox.translate_to_plot(crosshair),
+                    None => self.center(),
+                };
+                let cell: PVec2 = PVec2::new(
+                    (start.x as i32 + dx).clamp(0, u16::MAX as i32) as u16,
+                    (start.y as i32 + dy).clamp(0, u16::MAX as i32) as u16,
+                );
+                self.crosshair = Some(viewbox.translate_from_plot(cell));
+            }
+            Action::PlaceCrosshair(cell) => self.crosshair = Some(viewbox.translate_from_plot(cell)),
+            Action::ToggleCrosshair => {
+                self.crosshair = match self.crosshair {
+                    Some(_) => None,
+                    None => Some(viewbox.translate_from_plot(self.center())),
+                };
+            }
+            Action::Quit => return false,
+        }
+        true
+    }
+
+    /// Lay out the chart, with the crosshair behind it and the picked out point marked on top.
+    fn scene(&self) -> Scene {
+        let chart: Scene = self.chart.scene();
+        let mut scene: Scene = Scene::new(chart.width, chart.height);
+        let viewbox: ScaledViewBox = self.chart.base().viewbox();
+        let pick: Option<Pick> = self.pick(&viewbox);
+        if let Some(pick) = &pick {
+            let (position, size) = self.chart.base().data_area();
+            let center: Vec2 = viewbox.translate(pick.point);
+            let (left, right) = (position.x as f32, (position.x + size.x - 1) as f32);
+            let (top, bottom) = (position.y as f32, (position.y + size.y - 1) as f32);
+            let style: Style = Style::color(AXIS_COLOR);
+            scene.shapes(vec![Shape::Line(Vec2::new(center.x, top), Vec2::new(center.x, bottom))], '|', DotMode::Ascii, style);
+            scene.shapes(vec![Shape::Line(Vec2::new(left, center.y), Vec2::new(right, center.y))], '-', DotMode::Ascii, style);
+        }
+        scene.extend(&chart);
+        if let Some(pick) = &pick {
+            scene.shapes(vec![Shape::Point(viewbox.translate(pick.point))], '+', DotMode::Ascii, Style::PLAIN.bold());
+        }
+        scene
+    }
+
+    /// Get the text of the status line: the values of the point picked out by the crosshair, or
+    /// else the range of values shown on each axis, and the keys.
+    fn status(&self) -> String {
+        let (x, y) = (self.chart.base().x(), self.chart.base().y());
+        let values: String = match (self.crosshair, self.pick(&self.chart.base().viewbox())) {
+            (Some(_), Some(pick)) => {
+                let name: &str = if pick.series.name.is_empty() { "point" } else { &pick.series.name };
+                format!("{}: x = {}, y = {}", name, x.value_label(pick.point.x), y.value_label(pick.point.y))
+            }
+            (Some(_), None) => "no points in view".to_string(),
+            (None, _) => {
+                let range = |axis: &Axis| {
+                    let (min, max) = axis.bounds();
+                    format!("{} to {}", format_number(min), format_number(max))
+                };
+                format!("x: {}  y: {}", range(x), range(y))
+            }
+        };
+        format!("{}  |  {}", values, HELP)
+    }
+}
+
+/// Show a chart until the viewer is quit. The chart should already be laid out for the size of
+/// the terminal, less a line at the bottom for the status line; this is kept up to date if the
+/// terminal is resized.
+pub fn view(chart: &mut dyn Chart, width: u16, height: u16, colors: ColorDepth) -> io::Result<()> {
+    let mut screen: Screen = Screen::new(width, height, colors)?;
+    let mut viewer: Viewer = Viewer::new(chart);
+    loop {
+        screen.clear();
+        screen.draw_scene(&viewer.scene());
+        screen.put_str(&viewer.status(), &PVec2::new(0, screen.height().saturating_sub(1)));
+        screen.render();
+        let action: Option<Action> = match read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => action(key),
+            Event::Mouse(event) => mouse_action(event),
+            Event::Resize(width, height) => {
+                screen.set_size(width, height);
+                viewer.chart.base_mut().set_size(width, height.saturating_sub(1).max(1));
+                None
+            }
+            _ => None,
+        };
+        if let Some(action) = action {
+            if !viewer.apply(action) { return Ok(()) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::line::LineChart;
+    use crate::renderer::canvas::TextCanvas;
+
+    #[test]
+    fn keys() {
+        let key = |code: KeyCode| action(KeyEvent::new(code, KeyModifiers::NONE));
+        assert_eq!(key(KeyCode::Left), Some(Action::Move(Vec2::new(-PAN_STEP, 0.0), 1.0)));
+        assert_eq!(key(KeyCode::Char('+')), Some(Action::Move(Vec2::new(0.0, 0.0), ZOOM_STEP)));
+        assert_eq!(key(KeyCode::Char('r')), Some(Action::Reset));
+        assert_eq!(key(KeyCode::Char('q')), Some(Action::Quit));
+        assert_eq!(key(KeyCode::Char('c')), Some(Action::ToggleCrosshair));
+        assert_eq!(action(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Action::Quit));
+        assert_eq!(action(KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT)), Some(Action::MoveCrosshair(-1, 0)));
+    }
+
+    #[test]
+    fn crosshair_picks_the_nearest_point() {
+        let points: Vec<Vec2> = (0..10).map(|i| Vec2::new(i as f32, (i * i) as f32)).collect();
+        let mut chart: LineChart = LineChart::new(Box::new(TextCanvas::new(40, 12)), vec![Series::new("squares", points)]);
+        let mut viewer: Viewer = Viewer::new(&mut chart);
+        let cell: PVec2 = viewer.chart.base().viewbox().translate_to_plot(Vec2::new(6.2, 30.0));
+        viewer.apply(Action::PlaceCrosshair(cell));
+        assert_eq!(viewer.status(), format!("squares: x = 6, y = 36  |  {}", HELP));
+        // moving one cell at a time eventually reaches the next point
+        for _ in 0..4 { viewer.apply(Action::MoveCrosshair(1, 0)); }
+        assert!(viewer.status().starts_with("squares: x = 7, y = 49"));
+        assert!(!viewer.apply(Action::Quit));
+    }
+}